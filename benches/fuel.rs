@@ -0,0 +1,78 @@
+//! Compares the ways of computing the fuel including the fuel for the fuel on
+//! large generated inputs. Run with `cargo bench --bench fuel`.
+
+use std::time::{Duration, Instant};
+
+use aoc19::riddle::advent1::{FuelMemo, FuelModel};
+
+const MODULES: usize = 2_000_000;
+
+/// Generates masses with a linear congruential generator, so every run sees
+/// the same input.
+fn generate_masses(count: usize, min: i64, max: i64) -> Vec<i64> {
+    let mut state: u64 = 0x853c_49e6_748f_ea9b;
+    let range = (max - min) as u64 + 1;
+    (0..count)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            min + ((state >> 11) % range) as i64
+        })
+        .collect()
+}
+
+/// The recursive formulation `calculate_fuel_recursive` used to have.
+fn recursive(model: &FuelModel, mass: i64) -> i64 {
+    let increment = model.fuel(mass).unwrap();
+    if increment <= 0 {
+        0
+    } else {
+        increment + recursive(model, increment)
+    }
+}
+
+fn time<F: FnMut() -> i128>(name: &str, mut f: F) -> Duration {
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    println!("  {:<12} {:>12.3?}  (total {})", name, elapsed, result);
+    elapsed
+}
+
+fn main() {
+    let model = FuelModel::default();
+    let inputs = vec![
+        ("masses up to 200000", generate_masses(MODULES, 0, 200_000)),
+        (
+            "masses near i64::MAX",
+            generate_masses(MODULES, i64::MAX - 1_000_000, i64::MAX),
+        ),
+    ];
+
+    for (name, masses) in inputs {
+        println!("{} modules, {}:", masses.len(), name);
+        let recursive_time = time("recursive", || {
+            masses.iter().map(|&m| recursive(&model, m) as i128).sum()
+        });
+        let iterative_time = time("iterative", || {
+            masses
+                .iter()
+                .map(|&m| model.fuel_recursive(m).unwrap() as i128)
+                .sum()
+        });
+        let mut memo = FuelMemo::new(model);
+        let memo_time = time("memoized", || {
+            masses
+                .iter()
+                .map(|&m| memo.fuel_recursive(m).unwrap() as i128)
+                .sum()
+        });
+        println!(
+            "  speedup over recursive: iterative {:.2}x, memoized {:.2}x ({} masses memoized)",
+            recursive_time.as_secs_f64() / iterative_time.as_secs_f64(),
+            recursive_time.as_secs_f64() / memo_time.as_secs_f64(),
+            memo.len()
+        );
+    }
+}
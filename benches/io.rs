@@ -0,0 +1,118 @@
+//! Compares reading inputs line by line into a `String` each with the
+//! zero-copy `MappedFile` on large generated inputs. Run with
+//! `cargo bench --bench io`, and with `--features mmap` to memory-map them.
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use aoc19::io::{
+    ints_from_file, ints_from_str, lines_from_file, parse_file, MappedFile, Separator,
+};
+use aoc19::riddle::advent2::tape_from_str;
+use aoc19::riddle::advent3::{wires_from_str, WireReader};
+
+const INTS: usize = 2_000_000;
+const SEGMENTS: usize = 1_000_000;
+
+/// A linear congruential generator, so every run sees the same input.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, range: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (self.0 >> 11) % range
+    }
+}
+
+fn write_input(name: &str, write: impl Fn(&mut Vec<u8>, &mut Lcg)) -> String {
+    let path = env::temp_dir().join(format!("aoc19-bench-{}", name));
+    let mut contents = Vec::new();
+    write(&mut contents, &mut Lcg(0x853c_49e6_748f_ea9b));
+    fs::write(&path, contents).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+fn time<F: FnMut() -> usize>(name: &str, mut f: F) -> Duration {
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    println!("  {:<24} {:>12.3?}  ({} values)", name, elapsed, result);
+    elapsed
+}
+
+fn report_speedup(baseline: Duration, mapped: Duration) {
+    println!(
+        "  speedup of the mapped file: {:.2}x",
+        baseline.as_secs_f64() / mapped.as_secs_f64()
+    );
+}
+
+fn main() {
+    let ints = write_input("ints.txt", |out, rng| {
+        for _ in 0..INTS {
+            writeln!(out, "{}", rng.next(200_000)).unwrap();
+        }
+    });
+    println!("{} ints, one per line:", INTS);
+    let baseline = time("lines_from_file", || {
+        lines_from_file(&ints)
+            .unwrap()
+            .map(|line| line.unwrap().trim().parse::<i64>().unwrap())
+            .collect::<Vec<i64>>()
+            .len()
+    });
+    time("ints_from_file", || ints_from_file(&ints).unwrap().len());
+    let mapped = time("MappedFile", || {
+        let file = MappedFile::open(&ints).unwrap();
+        ints_from_str(file.as_str().unwrap(), &ints).unwrap().len()
+    });
+    report_speedup(baseline, mapped);
+
+    let tape = write_input("tape.txt", |out, rng| {
+        let values: Vec<String> = (0..INTS).map(|_| rng.next(100).to_string()).collect();
+        writeln!(out, "{}", values.join(",")).unwrap();
+    });
+    println!("{} Intcode values on one line:", INTS);
+    let baseline = time("parse_file", || {
+        parse_file::<i64>(&tape, Separator::Comma).unwrap().len()
+    });
+    let mapped = time("MappedFile", || {
+        let file = MappedFile::open(&tape).unwrap();
+        tape_from_str(file.as_str().unwrap(), &tape).unwrap().len()
+    });
+    report_speedup(baseline, mapped);
+
+    let wires = write_input("wires.txt", |out, rng| {
+        for _ in 0..2 {
+            let segments: Vec<String> = (0..SEGMENTS)
+                .map(|_| {
+                    let direction = ["U", "D", "L", "R"][rng.next(4) as usize];
+                    format!("{}{}", direction, rng.next(1000) + 1)
+                })
+                .collect();
+            writeln!(out, "{}", segments.join(",")).unwrap();
+        }
+    });
+    println!("2 wires of {} segments:", SEGMENTS);
+    let baseline = time("WireReader", || {
+        let reader = aoc19::io::reader_from_file(&wires).unwrap();
+        WireReader::new(reader, &wires)
+            .map(|wire| wire.unwrap().len())
+            .sum()
+    });
+    let mapped = time("MappedFile", || {
+        let file = MappedFile::open(&wires).unwrap();
+        let wires = wires_from_str(file.as_str().unwrap(), &wires).unwrap();
+        wires.iter().map(Vec::len).sum()
+    });
+    report_speedup(baseline, mapped);
+
+    for path in &[ints, tape, wires] {
+        let _ = fs::remove_file(path);
+    }
+}
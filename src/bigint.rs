@@ -0,0 +1,332 @@
+//! A minimal arbitrary-precision integer for answers that do not fit into an
+//! `i64`, without pulling in a dependency.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+
+/// The base of the limbs, a power of ten so printing needs no division.
+const BASE: u64 = 1_000_000_000;
+const BASE_DIGITS: usize = 9;
+
+/// An integer of any size.
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Default)]
+pub struct BigInt {
+    negative: bool,
+    /// The magnitude in base `BASE`, least significant limb first, without
+    /// leading zero limbs. Zero has no limbs and is never negative.
+    limbs: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn zero() -> BigInt {
+        BigInt::default()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// The integer as an `i64`, or `None` if it does not fit.
+    pub fn to_i64(&self) -> Option<i64> {
+        let mut magnitude: i128 = 0;
+        for &limb in self.limbs.iter().rev() {
+            magnitude = magnitude
+                .checked_mul(BASE as i128)?
+                .checked_add(limb as i128)?;
+        }
+        let value = if self.negative { -magnitude } else { magnitude };
+        i64::try_from(value).ok()
+    }
+
+    fn from_parts(negative: bool, mut limbs: Vec<u32>) -> BigInt {
+        while limbs.last() == Some(&0) {
+            limbs.pop();
+        }
+        BigInt {
+            negative: negative && !limbs.is_empty(),
+            limbs,
+        }
+    }
+}
+
+fn compare_magnitudes(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut sum = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0;
+    for i in 0..a.len().max(b.len()) {
+        let limb = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        sum.push((limb % BASE) as u32);
+        carry = limb / BASE;
+    }
+    if carry > 0 {
+        sum.push(carry as u32);
+    }
+    sum
+}
+
+/// Subtracts `b` from `a`, which must not be smaller.
+fn sub_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0;
+    for (i, &limb) in a.iter().enumerate() {
+        let subtrahend = *b.get(i).unwrap_or(&0) as i64 + borrow;
+        let mut limb = limb as i64 - subtrahend;
+        borrow = 0;
+        if limb < 0 {
+            limb += BASE as i64;
+            borrow = 1;
+        }
+        difference.push(limb as u32);
+    }
+    difference
+}
+
+impl From<i64> for BigInt {
+    fn from(n: i64) -> BigInt {
+        BigInt::from(n as i128)
+    }
+}
+
+impl From<u64> for BigInt {
+    fn from(n: u64) -> BigInt {
+        BigInt::from(n as i128)
+    }
+}
+
+impl From<i128> for BigInt {
+    fn from(n: i128) -> BigInt {
+        let mut magnitude = n.unsigned_abs();
+        let mut limbs = Vec::new();
+        while magnitude > 0 {
+            limbs.push((magnitude % BASE as u128) as u32);
+            magnitude /= BASE as u128;
+        }
+        BigInt::from_parts(n < 0, limbs)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseBigIntError(pub String);
+
+impl fmt::Display for ParseBigIntError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid integer '{}'", self.0)
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = ParseBigIntError;
+
+    /// Parses decimal integers with an optional sign.
+    fn from_str(s: &str) -> Result<BigInt, ParseBigIntError> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(ParseBigIntError(s.to_string()));
+        }
+        let limbs = digits
+            .as_bytes()
+            .rchunks(BASE_DIGITS)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .fold(0, |limb, digit| limb * 10 + (digit - b'0') as u32)
+            })
+            .collect();
+        Ok(BigInt::from_parts(negative, limbs))
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut limbs = self.limbs.iter().rev();
+        match limbs.next() {
+            Some(first) => {
+                let sign = if self.negative { "-" } else { "" };
+                write!(f, "{}{}", sign, first)?;
+            }
+            None => return write!(f, "0"),
+        }
+        for limb in limbs {
+            write!(f, "{:0width$}", limb, width = BASE_DIGITS)?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitudes(&self.limbs, &other.limbs),
+            (true, true) => compare_magnitudes(&other.limbs, &self.limbs),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.limbs)
+    }
+}
+
+impl<'a> Add<&'a BigInt> for &'a BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_magnitudes(&self.limbs, &other.limbs));
+        }
+        match compare_magnitudes(&self.limbs, &other.limbs) {
+            Ordering::Less => {
+                BigInt::from_parts(other.negative, sub_magnitudes(&other.limbs, &self.limbs))
+            }
+            _ => BigInt::from_parts(self.negative, sub_magnitudes(&self.limbs, &other.limbs)),
+        }
+    }
+}
+
+impl<'a> Sub<&'a BigInt> for &'a BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other.clone()
+    }
+}
+
+impl<'a> Mul<&'a BigInt> for &'a BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        let mut product = vec![0u64; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let limb = product[i + j] + a as u64 * b as u64 + carry;
+                product[i + j] = limb % BASE;
+                carry = limb / BASE;
+            }
+            product[i + other.limbs.len()] += carry;
+        }
+        let limbs = product.into_iter().map(|limb| limb as u32).collect();
+        BigInt::from_parts(self.negative != other.negative, limbs)
+    }
+}
+
+impl Add for BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: BigInt) -> BigInt {
+        &self + &other
+    }
+}
+
+impl Sub for BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: BigInt) -> BigInt {
+        &self - &other
+    }
+}
+
+impl Mul for BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: BigInt) -> BigInt {
+        &self * &other
+    }
+}
+
+#[cfg(test)]
+mod bigint_tests {
+    mod parse_tests {
+        use super::super::{BigInt, ParseBigIntError};
+
+        #[test]
+        fn it_reads_and_prints_integers() {
+            for text in &[
+                "0",
+                "7",
+                "-42",
+                "1000000000",
+                "-123456789012345678901234567890",
+            ] {
+                assert_eq!(text.parse::<BigInt>().unwrap().to_string(), *text);
+            }
+            assert_eq!("+007".parse::<BigInt>().unwrap().to_string(), "7");
+            assert_eq!("-0".parse::<BigInt>().unwrap(), BigInt::zero());
+        }
+
+        #[test]
+        fn it_rejects_invalid_integers() {
+            for text in &["", "-", "1e9", "12 3", "0x10"] {
+                assert_eq!(
+                    text.parse::<BigInt>(),
+                    Err(ParseBigIntError(text.to_string()))
+                );
+            }
+        }
+    }
+
+    mod arithmetic_tests {
+        use super::super::BigInt;
+
+        fn big(text: &str) -> BigInt {
+            text.parse().unwrap()
+        }
+
+        #[test]
+        fn it_adds_and_subtracts() {
+            let max = BigInt::from(i64::MAX);
+            assert_eq!((&max + &max).to_string(), "18446744073709551614");
+            assert_eq!(big("1000000000") - big("1"), big("999999999"));
+            assert_eq!(big("5") - big("12"), big("-7"));
+            assert_eq!(big("-5") + big("5"), BigInt::zero());
+            assert_eq!(big("-999999999") - big("1"), big("-1000000000"));
+        }
+
+        #[test]
+        fn it_multiplies() {
+            let factor = big("123456789123456789");
+            assert_eq!(
+                (&factor * &factor).to_string(),
+                "15241578780673678515622620750190521"
+            );
+            assert_eq!(big("-3") * big("4"), big("-12"));
+            assert_eq!(big("-3") * BigInt::zero(), BigInt::zero());
+        }
+
+        #[test]
+        fn it_compares_and_converts() {
+            assert!(big("-10") < big("-9"));
+            assert!(big("-1") < big("0"));
+            assert!(big("1000000000") > big("999999999"));
+            assert_eq!(BigInt::from(i64::MIN).to_i64(), Some(i64::MIN));
+            assert_eq!((BigInt::from(i64::MAX) + big("1")).to_i64(), None);
+            assert_eq!(BigInt::from(u64::MAX).to_string(), u64::MAX.to_string());
+        }
+    }
+}
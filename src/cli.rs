@@ -0,0 +1,1636 @@
+//! The command line of the `aoc19` binary. `main` parses the arguments,
+//! runs the command and returns the exit code of the process.
+
+use std::env;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::time::Duration;
+
+use super::io::Input;
+use super::json::Json;
+use super::riddle::answers::{diff, Answers};
+use super::riddle::bench::{read_results, regressions, write_results, BenchResult};
+use super::riddle::config::{Config, Settings, DEFAULT_CONFIG};
+use super::riddle::riddlecontainer::{RiddleContainer, RiddleRun};
+use super::riddle::{registered_riddles, RiddleError, SharedInputs, Solution, Timings};
+
+pub const EXIT_SUCCESS: i32 = 0;
+/// The exit code of `RiddleError::Generic`.
+pub const EXIT_FAILURE: i32 = 1;
+/// The exit code of invalid command lines.
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_UNKNOWN_RIDDLE: i32 = 3;
+pub const EXIT_IO: i32 = 4;
+pub const EXIT_ARGUMENTS_COUNT: i32 = 5;
+pub const EXIT_NO_SOLUTION: i32 = 6;
+/// The exit code of `verify` if a riddle gives another answer.
+pub const EXIT_WRONG_ANSWER: i32 = 7;
+pub const EXIT_INVALID_ARGUMENTS: i32 = 8;
+/// The exit code of `verify` if no answer is recorded for a riddle.
+pub const EXIT_NO_ANSWER: i32 = 9;
+/// The exit code of `bench` if a riddle got slower than in the baseline.
+pub const EXIT_REGRESSION: i32 = 10;
+pub const EXIT_MISSING_INPUT: i32 = 11;
+
+const DEFAULT_RUNS: usize = 10;
+const DEFAULT_WARMUP: usize = 1;
+/// How many percent slower than the baseline a riddle may get.
+const DEFAULT_THRESHOLD: usize = 10;
+
+/// The exit code for an error of a riddle. Every variant but
+/// `RiddleError::Parse` has its own code.
+pub fn exit_code(err: &RiddleError) -> i32 {
+    match err {
+        RiddleError::UnknownRiddle(_) => EXIT_UNKNOWN_RIDDLE,
+        RiddleError::IO(_) => EXIT_IO,
+        RiddleError::ArgumentsCount(_, _) => EXIT_ARGUMENTS_COUNT,
+        RiddleError::InvalidArguments { .. } => EXIT_INVALID_ARGUMENTS,
+        RiddleError::Generic(_) => EXIT_FAILURE,
+        RiddleError::NoSolutionFound => EXIT_NO_SOLUTION,
+        RiddleError::MissingInput(_) => EXIT_MISSING_INPUT,
+        // Failures while parsing keep the code of what went wrong.
+        RiddleError::Parse(err) => exit_code(err),
+    }
+}
+
+/// A command line that cannot be run.
+#[derive(Debug, PartialEq, Eq)]
+pub struct UsageError(pub String);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A riddle to solve with the input and the arguments of the riddle.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Target {
+    pub riddle: String,
+    /// The path of the input, `-` for stdin, or `None` for the default input.
+    pub input: Option<String>,
+    pub args: Vec<String>,
+}
+
+impl Target {
+    /// The input given on the command line, if any.
+    fn input(&self) -> Option<Input> {
+        self.input.as_ref().map(|path| {
+            if path == "-" {
+                Input::stdin()
+            } else {
+                Input::path(path)
+            }
+        })
+    }
+
+    fn solve(&self, riddles: &RiddleContainer) -> Result<Solution, RiddleError> {
+        match self.input() {
+            Some(input) => riddles.solve_riddle_with_input(self.riddle.clone(), input, &self.args),
+            None => riddles.solve_riddle(self.riddle.clone(), &self.args),
+        }
+    }
+
+    /// Solves the riddle like `solve`, timing parsing and solving apart.
+    fn solve_timed(&self, riddles: &RiddleContainer) -> Result<(Solution, Timings), RiddleError> {
+        let unknown = || RiddleError::UnknownRiddle(self.riddle.clone());
+        match self.input() {
+            Some(input) => {
+                let entry = riddles.entry(&self.riddle).ok_or_else(unknown)?;
+                (entry.build)(input, &mut SharedInputs::default()).solve_timed(&self.args)
+            }
+            None => riddles.riddle(&self.riddle)?.solve_timed(&self.args),
+        }
+    }
+
+    fn bench(
+        &self,
+        riddles: &RiddleContainer,
+        warmup: usize,
+        runs: usize,
+    ) -> Result<BenchResult, RiddleError> {
+        let riddle = self.riddle.clone();
+        match self.input() {
+            Some(input) => riddles.bench_riddle_with_input(riddle, input, &self.args, warmup, runs),
+            None => riddles.bench_riddle(riddle, &self.args, warmup, runs),
+        }
+    }
+}
+
+/// How `run` and `all` print the results of the riddles.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Format {
+    /// `result: <answer>` for `run` and a table for `all`.
+    Text,
+    /// Only the answer for `run`, and `<day.part> <answer>` per line for
+    /// `all`.
+    Plain,
+    /// A JSON object per riddle, see `record`. `all` wraps them in
+    /// `{"riddles": [...]}`.
+    Json,
+    /// A JSON object per riddle and line.
+    JsonLines,
+}
+
+/// What `verify` checks.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Verification {
+    /// The riddle to verify, or `None` for all riddles.
+    pub target: Option<Target>,
+    /// The expected answer, instead of the one in the answers file.
+    pub expected: Option<String>,
+    /// The path of the answers file, or `None` for the answers of the input
+    /// set.
+    pub answers: Option<String>,
+    /// Whether to ask to record answers that are missing or differ.
+    pub record: bool,
+    pub jobs: usize,
+}
+
+/// What `bench` measures.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Benchmark {
+    /// The riddle to benchmark, or `None` for all riddles.
+    pub target: Option<Target>,
+    pub runs: usize,
+    /// How often each riddle is solved before it is measured.
+    pub warmup: usize,
+    /// The path to write the results to as JSON.
+    pub output: Option<String>,
+    /// The path of results to compare with.
+    pub baseline: Option<String>,
+    /// How many percent slower than the baseline a riddle may get.
+    pub threshold: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Solves a riddle.
+    Run { target: Target, format: Format },
+    /// Lists the registered riddles.
+    List,
+    /// Solves all riddles with their default inputs on `jobs` threads.
+    All { jobs: usize, format: Format },
+    /// Checks that riddles give the expected answers.
+    Verify(Verification),
+    /// Solves riddles repeatedly and reports how long they took.
+    Bench(Benchmark),
+    /// Prints the usage, or the options of a riddle.
+    Help(Option<String>),
+}
+
+impl Command {
+    /// Parses the arguments after the name of the binary. For compatibility,
+    /// `<day.part> [input] [key=value...]` is the same as `run`.
+    pub fn parse(args: &[String]) -> Result<Command, UsageError> {
+        let mut positional = Vec::new();
+        let mut riddle_args = Vec::new();
+        let mut input = None;
+        let mut runs = None;
+        let mut warmup = None;
+        let mut output = None;
+        let mut baseline = None;
+        let mut threshold = None;
+        let mut jobs = None;
+        let mut format = None;
+        let mut answers = None;
+        let mut record = false;
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(Command::Help(None)),
+                "--input" => input = Some(value_of("--input", args.next())?),
+                "--runs" => runs = Some(positive("--runs", args.next())?),
+                "--warmup" => warmup = Some(count("--warmup", args.next())?),
+                "--output" => output = Some(value_of("--output", args.next())?),
+                "--baseline" => baseline = Some(value_of("--baseline", args.next())?),
+                "--threshold" => threshold = Some(count("--threshold", args.next())?),
+                "--jobs" => jobs = Some(positive("--jobs", args.next())?),
+                "--format" => format = Some(format_of(args.next())?),
+                "--answers" => answers = Some(value_of("--answers", args.next())?),
+                "--record" => record = true,
+                _ if arg.starts_with("--") => {
+                    return Err(UsageError(format!("unknown option '{}'", arg)))
+                }
+                _ if arg.contains('=') => riddle_args.push(arg.clone()),
+                _ => positional.push(arg.clone()),
+            }
+        }
+
+        let mut positional = positional.into_iter();
+        let command = match positional.next() {
+            Some(command) => command,
+            None => return Ok(Command::Help(None)),
+        };
+        if let "help" | "list" | "all" = command.as_str() {
+            if input.is_some() {
+                return Err(UsageError(
+                    "--input only applies to run, verify and bench".to_string(),
+                ));
+            }
+            if !riddle_args.is_empty() {
+                return Err(UsageError(
+                    "riddle arguments only apply to run, verify and bench".to_string(),
+                ));
+            }
+        }
+        let command = match command.as_str() {
+            "help" => Command::Help(positional.next()),
+            "list" => Command::List,
+            "all" => Command::All {
+                jobs: jobs.take().unwrap_or(1),
+                format: format.take().unwrap_or(Format::Text),
+            },
+            "run" => {
+                let riddle = riddle_of(&command, positional.next())?;
+                let input = input_of(input, positional.next())?;
+                Command::Run {
+                    target: Target {
+                        riddle,
+                        input,
+                        args: riddle_args,
+                    },
+                    format: format.take().unwrap_or(Format::Text),
+                }
+            }
+            "verify" => {
+                let riddle = positional.next();
+                let expected = positional.next();
+                if riddle.is_none() && (input.is_some() || !riddle_args.is_empty()) {
+                    return Err(UsageError(
+                        "--input and riddle arguments need a riddle to verify".to_string(),
+                    ));
+                }
+                if record && expected.is_some() {
+                    return Err(UsageError(
+                        "--record only records answers of the riddles, not given ones".to_string(),
+                    ));
+                }
+                // The answers of the data directory belong to its inputs.
+                if input.is_some() && expected.is_none() && answers.is_none() {
+                    return Err(UsageError(
+                        "--input needs the answer to check or --answers".to_string(),
+                    ));
+                }
+                Command::Verify(Verification {
+                    target: riddle.map(|riddle| Target {
+                        riddle,
+                        input,
+                        args: riddle_args,
+                    }),
+                    expected,
+                    answers: answers.take(),
+                    record: std::mem::take(&mut record),
+                    jobs: jobs.take().unwrap_or(1),
+                })
+            }
+            "bench" => {
+                let riddle = positional.next();
+                let input = input_of(input, positional.next())?;
+                if riddle.is_none() && (input.is_some() || !riddle_args.is_empty()) {
+                    return Err(UsageError(
+                        "--input and riddle arguments need a riddle to benchmark".to_string(),
+                    ));
+                }
+                Command::Bench(Benchmark {
+                    target: riddle.map(|riddle| Target {
+                        riddle,
+                        input,
+                        args: riddle_args,
+                    }),
+                    runs: runs.take().unwrap_or(DEFAULT_RUNS),
+                    warmup: warmup.take().unwrap_or(DEFAULT_WARMUP),
+                    output: output.take(),
+                    baseline: baseline.take(),
+                    threshold: threshold.take().unwrap_or(DEFAULT_THRESHOLD),
+                })
+            }
+            riddle if riddle.contains('.') => {
+                let input = input_of(input, positional.next())?;
+                Command::Run {
+                    target: Target {
+                        riddle: riddle.to_string(),
+                        input,
+                        args: riddle_args,
+                    },
+                    format: format.take().unwrap_or(Format::Text),
+                }
+            }
+            _ => return Err(UsageError(format!("unknown command '{}'", command))),
+        };
+
+        if let Some(arg) = positional.next() {
+            return Err(UsageError(format!("unexpected argument '{}'", arg)));
+        }
+        if runs.is_some()
+            || warmup.is_some()
+            || output.is_some()
+            || baseline.is_some()
+            || threshold.is_some()
+        {
+            return Err(UsageError(
+                "--runs, --warmup, --output, --baseline and --threshold only apply to bench"
+                    .to_string(),
+            ));
+        }
+        if jobs.is_some() {
+            return Err(UsageError(
+                "--jobs only applies to all and verify".to_string(),
+            ));
+        }
+        if format.is_some() {
+            return Err(UsageError(
+                "--format only applies to run and all".to_string(),
+            ));
+        }
+        if answers.is_some() || record {
+            return Err(UsageError(
+                "--answers and --record only apply to verify".to_string(),
+            ));
+        }
+        Ok(command)
+    }
+}
+
+fn value_of(option: &str, value: Option<&String>) -> Result<String, UsageError> {
+    value
+        .cloned()
+        .ok_or_else(|| UsageError(format!("{} expects a value", option)))
+}
+
+fn positive(option: &str, value: Option<&String>) -> Result<usize, UsageError> {
+    let value = value_of(option, value)?;
+    match value.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(UsageError(format!(
+            "{} expects a positive number, got '{}'",
+            option, value
+        ))),
+    }
+}
+
+fn format_of(value: Option<&String>) -> Result<Format, UsageError> {
+    let value = value_of("--format", value)?;
+    match value.as_str() {
+        "text" => Ok(Format::Text),
+        "plain" => Ok(Format::Plain),
+        "json" => Ok(Format::Json),
+        "jsonl" => Ok(Format::JsonLines),
+        _ => Err(UsageError(format!(
+            "--format expects text, plain, json or jsonl, got '{}'",
+            value
+        ))),
+    }
+}
+
+fn count(option: &str, value: Option<&String>) -> Result<usize, UsageError> {
+    let value = value_of(option, value)?;
+    value.parse::<usize>().map_err(|_| {
+        UsageError(format!(
+            "{} expects a number of zero or more, got '{}'",
+            option, value
+        ))
+    })
+}
+
+fn riddle_of(command: &str, riddle: Option<String>) -> Result<String, UsageError> {
+    riddle.ok_or_else(|| UsageError(format!("{} expects a riddle like 1.2", command)))
+}
+
+/// The input is either given by `--input` or as the argument after the
+/// riddle, but not both.
+fn input_of(
+    option: Option<String>,
+    positional: Option<String>,
+) -> Result<Option<String>, UsageError> {
+    match (option, positional) {
+        (Some(_), Some(arg)) => Err(UsageError(format!(
+            "unexpected argument '{}', the input is given by --input",
+            arg
+        ))),
+        (option, positional) => Ok(option.or(positional)),
+    }
+}
+
+/// The options of every command, which choose where the inputs are read
+/// from.
+#[derive(Debug, PartialEq, Eq, Default)]
+pub struct Globals {
+    /// The config file given by `--config`.
+    pub config: Option<String>,
+    /// The settings given by `--data-dir` and `--profile`.
+    pub settings: Settings,
+}
+
+impl Globals {
+    /// Takes the global options out of `args`, wherever they are, and
+    /// returns them with the other arguments.
+    pub fn extract(args: &[String]) -> Result<(Globals, Vec<String>), UsageError> {
+        let mut globals = Globals::default();
+        let mut rest = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--config" => globals.config = Some(value_of("--config", args.next())?),
+                "--data-dir" => {
+                    globals.settings.data_dir = Some(value_of("--data-dir", args.next())?)
+                }
+                "--profile" => globals.settings.profile = Some(value_of("--profile", args.next())?),
+                _ => rest.push(arg.clone()),
+            }
+        }
+        Ok((globals, rest))
+    }
+
+    /// The configuration with these options, then the environment looked up
+    /// with `env`, then the config file, see `Config::load`.
+    pub fn load(
+        self,
+        default_config: Option<&str>,
+        env: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Config, RiddleError> {
+        Config::load(self.settings, self.config.as_deref(), default_config, env)
+    }
+}
+
+/// Runs the command line `args`, without the name of the binary, and
+/// returns the exit code. `stdin` answers the questions of `verify --record`.
+/// The settings are read from the environment of the process and from
+/// `./aoc19.conf`.
+pub fn main(
+    args: &[String],
+    stdin: &mut dyn BufRead,
+    out: &mut dyn Write,
+    err: &mut dyn Write,
+) -> i32 {
+    let env = |name: &str| env::var(name).ok();
+    main_with_env(args, &env, Some(DEFAULT_CONFIG), stdin, out, err)
+}
+
+/// Runs the command line like `main`, but looks up the environment with
+/// `env` and reads `default_config`, if it exists, when no config file is
+/// given.
+pub fn main_with_env(
+    args: &[String],
+    env: &dyn Fn(&str) -> Option<String>,
+    default_config: Option<&str>,
+    stdin: &mut dyn BufRead,
+    out: &mut dyn Write,
+    err: &mut dyn Write,
+) -> i32 {
+    let (globals, args) = match Globals::extract(args) {
+        Ok(extracted) => extracted,
+        Err(usage) => return usage_error(err, &usage, &RiddleContainer::new()).unwrap_or(EXIT_IO),
+    };
+    let config = match globals.load(default_config, env) {
+        Ok(config) => config,
+        Err(riddle_err) => {
+            let result = writeln!(err, "error: {}", riddle_err);
+            return result.map_or(EXIT_IO, |_| exit_code(&riddle_err));
+        }
+    };
+    let riddles = RiddleContainer::with_config(config, registered_riddles());
+    let result = match Command::parse(&args) {
+        Ok(command) => execute(&riddles, command, stdin, out, err),
+        Err(usage) => usage_error(err, &usage, &riddles),
+    };
+    result.unwrap_or(EXIT_IO)
+}
+
+fn usage_error(
+    err: &mut dyn Write,
+    usage: &UsageError,
+    riddles: &RiddleContainer,
+) -> io::Result<i32> {
+    writeln!(err, "error: {}\n\n{}", usage, help(riddles))?;
+    Ok(EXIT_USAGE)
+}
+
+fn execute(
+    riddles: &RiddleContainer,
+    command: Command,
+    stdin: &mut dyn BufRead,
+    out: &mut dyn Write,
+    err: &mut dyn Write,
+) -> io::Result<i32> {
+    match command {
+        Command::Help(None) => {
+            write!(out, "{}", help(riddles))?;
+            Ok(EXIT_SUCCESS)
+        }
+        Command::Help(Some(riddle)) => match riddles.entry(&riddle) {
+            Some(entry) => {
+                writeln!(out, "{}  {}\n\noptions:", entry.key(), entry.name)?;
+                write!(out, "{}", entry.args.usage())?;
+                Ok(EXIT_SUCCESS)
+            }
+            None => report(err, &riddle, &RiddleError::UnknownRiddle(riddle.clone())),
+        },
+        Command::List => {
+            for entry in riddles.entries() {
+                writeln!(out, "{}  {}", entry.key(), entry.name)?;
+            }
+            Ok(EXIT_SUCCESS)
+        }
+        Command::Run { target, format } => {
+            let result = target.solve_timed(riddles);
+            if let Ok((solution, _)) = &result {
+                write_warnings(err, &target.riddle, solution)?;
+                // Only the text format shares stdout with the report.
+                if format == Format::Text {
+                    write_report(out, solution)?;
+                } else {
+                    write_report(err, solution)?;
+                }
+            }
+            match (format, result) {
+                (Format::Text, Ok((solution, _))) => {
+                    let answer = solution.to_string();
+                    if answer.contains('\n') {
+                        writeln!(out, "result:\n{}", answer)?;
+                    } else {
+                        writeln!(out, "result: {}", answer)?;
+                    }
+                    Ok(EXIT_SUCCESS)
+                }
+                (Format::Plain, Ok((solution, _))) => {
+                    writeln!(out, "{}", solution)?;
+                    Ok(EXIT_SUCCESS)
+                }
+                (Format::Text, Err(riddle_err)) | (Format::Plain, Err(riddle_err)) => {
+                    report(err, &target.riddle, &riddle_err)
+                }
+                (Format::Json, result) | (Format::JsonLines, result) => {
+                    let (solution, timings) = match &result {
+                        Ok((solution, timings)) => (Ok(solution), Some(timings)),
+                        Err(riddle_err) => (Err(riddle_err), None),
+                    };
+                    writeln!(out, "{}", record(&target.riddle, solution, timings))?;
+                    Ok(result.as_ref().err().map_or(EXIT_SUCCESS, exit_code))
+                }
+            }
+        }
+        Command::All { jobs, format } => write_runs(out, err, &riddles.solve_all(jobs), format),
+        Command::Verify(verification) => verify(riddles, verification, stdin, out, err),
+        Command::Bench(benchmark) => bench(riddles, benchmark, out, err),
+    }
+}
+
+/// Checks the answers of the riddles against the expected or the recorded
+/// ones. With `record`, asks whether to record answers that are missing or
+/// differ, and writes them back to the answers file.
+fn verify(
+    riddles: &RiddleContainer,
+    verification: Verification,
+    stdin: &mut dyn BufRead,
+    out: &mut dyn Write,
+    err: &mut dyn Write,
+) -> io::Result<i32> {
+    let path = match &verification.answers {
+        Some(path) => path.clone(),
+        None => riddles.config().answers_path(),
+    };
+    // Only a missing file starts out empty: recording over a file that
+    // cannot be parsed would overwrite the answers in it.
+    let mut answers = match Answers::from_file(&path) {
+        Ok(answers) => answers,
+        Err(RiddleError::IO(io_err))
+            if io_err.kind() == io::ErrorKind::NotFound
+                && (verification.record || verification.expected.is_some()) =>
+        {
+            Answers::default()
+        }
+        Err(riddle_err) => return report(err, &path, &riddle_err),
+    };
+    let results: Vec<(String, Result<Solution, RiddleError>)> = match &verification.target {
+        Some(target) => vec![(target.riddle.clone(), target.solve(riddles))],
+        None => riddles
+            .solve_all(verification.jobs)
+            .into_iter()
+            .map(|run| (run.entry.key(), run.result))
+            .collect(),
+    };
+
+    let mut code = EXIT_SUCCESS;
+    let (mut wrong, mut missing, mut failed, mut recorded) = (0, 0, 0, 0);
+    for (key, result) in &results {
+        let actual = match result {
+            Ok(solution) => {
+                write_warnings(err, key, solution)?;
+                write_report(err, solution)?;
+                solution.to_string()
+            }
+            Err(riddle_err) => {
+                failed += 1;
+                let failure = report(err, key, riddle_err)?;
+                code = if code == EXIT_SUCCESS { failure } else { code };
+                continue;
+            }
+        };
+        let expected = match &verification.expected {
+            Some(expected) => Some(expected.as_str()),
+            None => answers.get(key),
+        };
+        let failure = match expected {
+            Some(expected) if expected == actual => {
+                writeln!(out, "{}: ok", key)?;
+                continue;
+            }
+            Some(expected) => {
+                writeln!(out, "{}: wrong answer", key)?;
+                write!(out, "{}", diff(expected, &actual))?;
+                EXIT_WRONG_ANSWER
+            }
+            None => {
+                writeln!(out, "{}: no answer recorded, got {}", key, actual)?;
+                EXIT_NO_ANSWER
+            }
+        };
+        // The answers file has one line per answer, so images that cannot
+        // be read as letters cannot be recorded.
+        let recordable = verification.record && !actual.contains('\n');
+        if recordable && confirm(stdin, out, &format!("record {} = {}?", key, actual))? {
+            answers.set(key, &actual);
+            recorded += 1;
+            continue;
+        }
+        if failure == EXIT_WRONG_ANSWER {
+            wrong += 1;
+        } else {
+            missing += 1;
+        }
+        code = if code == EXIT_SUCCESS { failure } else { code };
+    }
+
+    if recorded > 0 {
+        if let Err(riddle_err) = answers.write_to_file(&path) {
+            return report(err, &path, &riddle_err);
+        }
+        writeln!(out, "recorded {} answers in {}", recorded, path)?;
+    }
+    writeln!(
+        out,
+        "{} riddles, {} wrong, {} without an answer, {} failed",
+        results.len(),
+        wrong,
+        missing,
+        failed
+    )?;
+    Ok(code)
+}
+
+/// Benchmarks the riddles, prints a table of the results and writes them
+/// to the output file. Riddles that got slower than in the baseline by more
+/// than the threshold are regressions.
+fn bench(
+    riddles: &RiddleContainer,
+    benchmark: Benchmark,
+    out: &mut dyn Write,
+    err: &mut dyn Write,
+) -> io::Result<i32> {
+    let baseline = match benchmark.baseline.as_deref().map(read_results) {
+        Some(Ok(baseline)) => Some(baseline),
+        Some(Err(riddle_err)) => {
+            return report(err, benchmark.baseline.as_deref().unwrap(), &riddle_err)
+        }
+        None => None,
+    };
+    let (warmup, runs) = (benchmark.warmup, benchmark.runs);
+    let benched: Vec<(String, Result<BenchResult, RiddleError>)> = match &benchmark.target {
+        Some(target) => vec![(target.riddle.clone(), target.bench(riddles, warmup, runs))],
+        None => riddles
+            .entries()
+            .map(|entry| {
+                let result = riddles.bench_riddle(entry.key(), &[], warmup, runs);
+                (entry.key(), result)
+            })
+            .collect(),
+    };
+
+    let mut code = EXIT_SUCCESS;
+    let mut results = Vec::new();
+    for (key, result) in benched {
+        match result {
+            Ok(result) => results.push(result),
+            Err(riddle_err) => {
+                let failure = report(err, &key, &riddle_err)?;
+                code = if code == EXIT_SUCCESS { failure } else { code };
+            }
+        }
+    }
+    let header = [
+        "riddle", "runs", "min", "median", "mean", "stddev", "parse", "solve",
+    ];
+    let rows: Vec<Vec<String>> = results
+        .iter()
+        .map(|result| {
+            vec![
+                result.riddle.clone(),
+                result.runs.to_string(),
+                format!("{:.3?}", result.total.min),
+                format!("{:.3?}", result.total.median),
+                format!("{:.3?}", result.total.mean),
+                format!("{:.3?}", result.total.stddev),
+                result
+                    .parse
+                    .map_or("-".to_string(), |parse| format!("{:.3?}", parse.median)),
+                format!("{:.3?}", result.solve.median),
+            ]
+        })
+        .collect();
+    write_table(out, &header, &rows, &[0])?;
+
+    if let Some(output) = &benchmark.output {
+        if let Err(riddle_err) = write_results(output, &results) {
+            return report(err, output, &riddle_err);
+        }
+        writeln!(out, "wrote the results to {}", output)?;
+    }
+    if let Some(baseline) = &baseline {
+        let threshold = benchmark.threshold as f64;
+        for regression in regressions(&results, baseline, threshold) {
+            writeln!(
+                out,
+                "{}: regression, median {:.3?} instead of {:.3?} (+{:.1}%)",
+                regression.riddle,
+                regression.current,
+                regression.baseline,
+                regression.slowdown()
+            )?;
+            code = if code == EXIT_SUCCESS {
+                EXIT_REGRESSION
+            } else {
+                code
+            };
+        }
+    }
+    Ok(code)
+}
+
+/// Asks a yes-or-no question on `out` and reads the reply from `stdin`.
+/// Anything but `y` or `yes` is a no.
+fn confirm(stdin: &mut dyn BufRead, out: &mut dyn Write, question: &str) -> io::Result<bool> {
+    write!(out, "{} [y/N] ", question)?;
+    out.flush()?;
+    let mut reply = String::new();
+    stdin.read_line(&mut reply)?;
+    let reply = reply.trim().to_lowercase();
+    Ok(reply == "y" || reply == "yes")
+}
+
+/// Writes the runs of `all` in the format. In the text and plain formats,
+/// the errors of the riddles that failed follow on `err`. The exit code is
+/// that of the first failure.
+fn write_runs(
+    out: &mut dyn Write,
+    err: &mut dyn Write,
+    runs: &[RiddleRun],
+    format: Format,
+) -> io::Result<i32> {
+    for run in runs {
+        if let Ok(solution) = &run.result {
+            write_warnings(err, &run.entry.key(), solution)?;
+            write_report(err, solution)?;
+        }
+    }
+    let records = runs
+        .iter()
+        .map(|run| record(&run.entry.key(), run.result.as_ref(), Some(&run.timings)));
+    match format {
+        Format::Text => return write_summary(out, err, runs),
+        Format::Plain => {
+            for run in runs {
+                match &run.result {
+                    Ok(solution) => writeln!(out, "{} {}", run.entry.key(), solution)?,
+                    Err(riddle_err) => {
+                        report(err, &run.entry.key(), riddle_err)?;
+                    }
+                }
+            }
+        }
+        Format::Json => {
+            let riddles = Json::Array(records.collect());
+            writeln!(out, "{}", Json::object(vec![("riddles", riddles)]))?;
+        }
+        Format::JsonLines => {
+            for record in records {
+                writeln!(out, "{}", record)?;
+            }
+        }
+    }
+    let failure = runs.iter().find_map(|run| run.result.as_ref().err());
+    Ok(failure.map_or(EXIT_SUCCESS, exit_code))
+}
+
+/// The result of a riddle as a JSON object with the key of the riddle, the
+/// answer as a string, the timings and the error. Either the answer or the
+/// error is `null`, and so are the timings of riddles that failed before
+/// they were timed.
+fn record(
+    riddle: &str,
+    result: Result<&Solution, &RiddleError>,
+    timings: Option<&Timings>,
+) -> Json {
+    let (answer, error) = match result {
+        Ok(solution) => (Json::string(solution.to_string()), Json::Null),
+        Err(riddle_err) => (Json::Null, riddle_err.to_json()),
+    };
+    Json::object(vec![
+        ("riddle", Json::string(riddle)),
+        ("answer", answer),
+        ("timings", timings.map_or(Json::Null, Timings::to_json)),
+        ("error", error),
+    ])
+}
+
+/// Writes a table of the runs, followed by the errors of the riddles that
+/// failed. The exit code is that of the first failure.
+pub fn write_summary(
+    out: &mut dyn Write,
+    err: &mut dyn Write,
+    runs: &[RiddleRun],
+) -> io::Result<i32> {
+    let header = ["day", "part", "answer", "time", "status"];
+    let rows: Vec<Vec<String>> = runs
+        .iter()
+        .map(|run| {
+            let (answer, status) = match &run.result {
+                Ok(solution) => (solution.to_string(), "ok"),
+                Err(_) => ("-".to_string(), "failed"),
+            };
+            vec![
+                run.entry.day.to_string(),
+                run.entry.part.to_string(),
+                answer,
+                format!("{:.3?}", run.timings.total()),
+                status.to_string(),
+            ]
+        })
+        .collect();
+    write_table(out, &header, &rows, &[2, 4])?;
+
+    let failed: Vec<&RiddleRun> = runs.iter().filter(|run| run.result.is_err()).collect();
+    let total: Duration = runs.iter().map(|run| run.timings.total()).sum();
+    writeln!(
+        out,
+        "{} riddles, {} failed, {:.3?} in total",
+        runs.len(),
+        failed.len(),
+        total
+    )?;
+    let mut code = EXIT_SUCCESS;
+    for run in failed {
+        if let Err(riddle_err) = &run.result {
+            let failure = report(err, &run.entry.key(), riddle_err)?;
+            if code == EXIT_SUCCESS {
+                code = failure;
+            }
+        }
+    }
+    Ok(code)
+}
+
+/// Writes a table with a header, padding the columns to the same width.
+/// Cells are aligned to the right, except in the columns `left`.
+fn write_table(
+    out: &mut dyn Write,
+    header: &[&str],
+    rows: &[Vec<String>],
+    left: &[usize],
+) -> io::Result<()> {
+    let header: Vec<String> = header.iter().map(|cell| cell.to_string()).collect();
+    let mut widths = vec![0; header.len()];
+    for row in Some(&header).into_iter().chain(rows) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in Some(&header).into_iter().chain(rows) {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(column, (cell, &width))| {
+                if left.contains(&column) {
+                    format!("{:<width$}", cell, width = width)
+                } else {
+                    format!("{:>width$}", cell, width = width)
+                }
+            })
+            .collect();
+        writeln!(out, "{}", cells.join("  ").trim_end())?;
+    }
+    Ok(())
+}
+
+/// Writes the warnings of a solution, which are not part of its answer.
+fn write_warnings(err: &mut dyn Write, riddle: &str, solution: &Solution) -> io::Result<()> {
+    for warning in solution.notes().iter().flat_map(|notes| &notes.warnings) {
+        writeln!(err, "warning: {}: {}", riddle, warning)?;
+    }
+    Ok(())
+}
+
+/// Writes the report a solution was asked for, which is not part of its
+/// answer either.
+fn write_report(to: &mut dyn Write, solution: &Solution) -> io::Result<()> {
+    if let Some(report) = solution.notes().and_then(|notes| notes.report.as_ref()) {
+        writeln!(to, "{}", report)?;
+    }
+    Ok(())
+}
+
+fn report(err: &mut dyn Write, riddle: &str, riddle_err: &RiddleError) -> io::Result<i32> {
+    writeln!(err, "error: {}: {}", riddle, riddle_err)?;
+    Ok(exit_code(riddle_err))
+}
+
+/// The usage of the binary with the registered riddles.
+pub fn help(riddles: &RiddleContainer) -> String {
+    let mut help = String::from(
+        "usage: aoc19 <command> [options] [key=value...]
+
+commands:
+  run <day.part>              solves a riddle
+  list                        lists the riddles
+  all                         solves all riddles and prints a summary
+  verify [day.part] [answer]  checks the answers of the riddles
+  bench [day.part]            times a riddle, or all riddles, over several runs
+  help [day.part]             prints this help, or the options of a riddle
+
+options:
+  --input <path>     reads the input from <path>, or from stdin for -
+  --runs <n>         how often bench times a riddle (default 10)
+  --warmup <n>       how often bench solves a riddle before timing it (default 1)
+  --output <path>    writes the results of bench to <path> as JSON
+  --baseline <path>  compares the results of bench with those in <path>
+  --threshold <n>    how many percent slower than the baseline a riddle may get (default 10)
+  --jobs <n>         how many riddles all and verify solve in parallel (default 1)
+  --format <format>  how run and all print results: text, plain, json or jsonl (default text)
+  --answers <path>   the answers verify checks (default <data-dir>/answers/<profile or default>.txt,
+                     which only holds answers for the inputs in <data-dir>)
+  --record           asks to record answers that are missing or differ
+  --data-dir <path>  where the inputs and answers are (default $AOC19_DATA_DIR or ./data)
+  --profile <name>   reads the inputs from <data-dir>/input/<name>/ (default $AOC19_PROFILE)
+  --config <path>    reads data-dir and profile from <path> (default $AOC19_CONFIG or ./aoc19.conf)
+  -h, --help         prints this help
+
+riddles:
+",
+    );
+    for entry in riddles.entries() {
+        help.push_str(&format!(
+            "  {}  {} ({})\n",
+            entry.key(),
+            entry.name,
+            riddles.input_path(entry)
+        ));
+    }
+    help
+}
+
+#[cfg(test)]
+mod cli_tests {
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    mod parse_tests {
+        use super::super::{Command, Format, Globals, Target, UsageError};
+        use super::strings;
+
+        fn parse(args: &[&str]) -> Result<Command, UsageError> {
+            Command::parse(&strings(args))
+        }
+
+        #[test]
+        fn it_parses_run() {
+            assert_eq!(
+                parse(&["run", "3.1", "--input", "wires.txt", "metric=euclidean"]),
+                Ok(Command::Run {
+                    target: Target {
+                        riddle: "3.1".to_string(),
+                        input: Some("wires.txt".to_string()),
+                        args: strings(&["metric=euclidean"]),
+                    },
+                    format: Format::Text,
+                })
+            );
+        }
+
+        #[test]
+        fn it_parses_the_format() {
+            match parse(&["run", "1.1", "--format", "jsonl"]) {
+                Ok(Command::Run { format, .. }) => assert_eq!(format, Format::JsonLines),
+                other => panic!("expected run, got {:?}", other),
+            }
+            assert_eq!(
+                parse(&["all", "--format", "plain"]),
+                Ok(Command::All {
+                    jobs: 1,
+                    format: Format::Plain
+                })
+            );
+            assert_eq!(
+                parse(&["1.1", "--format", "json"]),
+                parse(&["run", "1.1", "--format", "json"])
+            );
+        }
+
+        #[test]
+        fn it_keeps_the_old_form() {
+            assert_eq!(parse(&["1.2", "-"]), parse(&["run", "1.2", "--input", "-"]));
+            assert_eq!(parse(&["1.2"]), parse(&["run", "1.2"]));
+        }
+
+        #[test]
+        fn it_extracts_the_global_options() {
+            let args = strings(&["run", "--profile", "bob", "1.1", "--data-dir", "/srv/aoc"]);
+            let (globals, rest) = Globals::extract(&args).unwrap();
+            assert_eq!(rest, strings(&["run", "1.1"]));
+            assert_eq!(globals.settings.profile.unwrap(), "bob");
+            assert_eq!(globals.settings.data_dir.unwrap(), "/srv/aoc");
+            assert_eq!(globals.config, None);
+            assert_eq!(
+                Globals::extract(&strings(&["list", "--config"])),
+                Err(UsageError("--config expects a value".to_string()))
+            );
+        }
+
+        #[test]
+        fn it_parses_the_other_commands() {
+            assert_eq!(parse(&["list"]), Ok(Command::List));
+            let all = |jobs| {
+                Ok(Command::All {
+                    jobs,
+                    format: Format::Text,
+                })
+            };
+            assert_eq!(parse(&["all"]), all(1));
+            assert_eq!(parse(&["all", "--jobs", "4"]), all(4));
+            assert_eq!(parse(&[]), Ok(Command::Help(None)));
+            assert_eq!(parse(&["run", "1.1", "--help"]), Ok(Command::Help(None)));
+            assert_eq!(
+                parse(&["help", "2.2"]),
+                Ok(Command::Help(Some("2.2".to_string())))
+            );
+            match parse(&["bench", "2.2", "--runs", "3", "--warmup", "0"]) {
+                Ok(Command::Bench(benchmark)) => {
+                    assert_eq!(benchmark.target.unwrap().riddle, "2.2");
+                    assert_eq!((benchmark.runs, benchmark.warmup), (3, 0));
+                    assert_eq!(benchmark.threshold, 10);
+                }
+                other => panic!("expected bench, got {:?}", other),
+            }
+            let args = ["bench", "--output", "new.json", "--baseline", "old.json"];
+            match parse(&args[..]) {
+                Ok(Command::Bench(benchmark)) => {
+                    assert_eq!(benchmark.target, None);
+                    assert_eq!(benchmark.output.unwrap(), "new.json");
+                    assert_eq!(benchmark.baseline.unwrap(), "old.json");
+                }
+                other => panic!("expected bench, got {:?}", other),
+            }
+            match parse(&["verify", "2.1", "4023471"]) {
+                Ok(Command::Verify(verification)) => {
+                    assert_eq!(verification.expected.unwrap(), "4023471")
+                }
+                other => panic!("expected verify, got {:?}", other),
+            }
+            for args in &[
+                &["verify", "1.1", "2", "--input", "small.txt"][..],
+                &[
+                    "verify",
+                    "1.1",
+                    "--input",
+                    "small.txt",
+                    "--answers",
+                    "small-answers.txt",
+                ],
+            ] {
+                assert!(matches!(parse(args), Ok(Command::Verify(_))), "{:?}", args);
+            }
+            match parse(&["verify", "--record", "--answers", "mine.txt", "--jobs", "2"]) {
+                Ok(Command::Verify(verification)) => {
+                    assert_eq!(verification.target, None);
+                    assert_eq!(verification.answers.unwrap(), "mine.txt");
+                    assert!(verification.record);
+                    assert_eq!(verification.jobs, 2);
+                }
+                other => panic!("expected verify, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn it_rejects_invalid_usage() {
+            let invalid: &[&[&str]] = &[
+                &["frobnicate"],
+                &["run"],
+                &["run", "1.1", "--input"],
+                &["run", "1.1", "--input", "a.txt", "b.txt"],
+                &["run", "1.1", "a.txt", "b.txt"],
+                &["run", "1.1", "--runs", "2"],
+                &["bench", "1.1", "--runs", "0"],
+                &["bench", "1.1", "--warmup", "-1"],
+                &["bench", "--input", "a.txt"],
+                &["run", "1.1", "--baseline", "old.json"],
+                &["all", "--threshold", "5"],
+                &["run", "1.1", "--format", "xml"],
+                &["run", "1.1", "--format"],
+                &["verify", "--format", "json"],
+                &["all", "--jobs", "0"],
+                &["run", "1.1", "--jobs", "2"],
+                &["list", "--input", "a.txt"],
+                &["all", "metric=euclidean"],
+                &["verify", "--input", "a.txt"],
+                &["verify", "metric=euclidean"],
+                &["verify", "1.1", "3210097", "--record"],
+                &["verify", "1.1", "--input", "a.txt"],
+                &["verify", "1.1", "--input", "a.txt", "--record"],
+                &["run", "1.1", "--record"],
+                &["all", "--answers", "mine.txt"],
+                &["run", "1.1", "--verbose"],
+            ];
+            for args in invalid {
+                assert!(parse(args).is_err(), "{:?}", args);
+            }
+        }
+    }
+
+    mod main_tests {
+        use super::super::super::json::Json;
+        use super::super::super::riddle::RiddleError;
+        use super::super::super::testing::TempDir;
+        use super::super::{
+            exit_code, main_with_env, EXIT_FAILURE, EXIT_INVALID_ARGUMENTS, EXIT_IO,
+            EXIT_MISSING_INPUT, EXIT_NO_ANSWER, EXIT_REGRESSION, EXIT_SUCCESS, EXIT_UNKNOWN_RIDDLE,
+            EXIT_USAGE, EXIT_WRONG_ANSWER,
+        };
+        use super::strings;
+        use std::fs;
+
+        fn run(args: &[&str]) -> (i32, String, String) {
+            run_with_stdin(args, "")
+        }
+
+        fn run_with_stdin(args: &[&str], stdin: &str) -> (i32, String, String) {
+            run_with_env(args, stdin, &|_| None)
+        }
+
+        /// Runs `args` with the environment `env` instead of the one of the
+        /// process, and without `./aoc19.conf`.
+        fn run_with_env(
+            args: &[&str],
+            stdin: &str,
+            env: &dyn Fn(&str) -> Option<String>,
+        ) -> (i32, String, String) {
+            let mut out = Vec::new();
+            let mut err = Vec::new();
+            let code = main_with_env(
+                &strings(args),
+                env,
+                None,
+                &mut stdin.as_bytes(),
+                &mut out,
+                &mut err,
+            );
+            (
+                code,
+                String::from_utf8(out).unwrap(),
+                String::from_utf8(err).unwrap(),
+            )
+        }
+
+        #[test]
+        fn it_solves_riddles() {
+            assert_eq!(
+                run(&["run", "2.1"]),
+                (EXIT_SUCCESS, "result: 4023471\n".to_string(), String::new())
+            );
+        }
+
+        #[test]
+        fn it_warns_about_skipped_lines() {
+            let args = [
+                "run",
+                "1.1",
+                "--input",
+                "./data/test/advent1/invalid_masses.txt",
+                "lenient=true",
+                "--format",
+                "plain",
+            ];
+            let (code, out, err) = run(&args);
+            assert_eq!((code, out.as_str()), (EXIT_SUCCESS, "4\n"));
+            assert_eq!(
+                err,
+                "warning: 1.1: skipped 2 invalid line(s) in ./data/test/advent1/invalid_masses.txt\n"
+            );
+        }
+
+        #[test]
+        fn it_keeps_reports_out_of_json() {
+            let input = "./data/test/advent1/masses.txt";
+            let args = [
+                "run",
+                "1.1",
+                "--input",
+                input,
+                "report=csv",
+                "--format",
+                "json",
+            ];
+            let (code, out, err) = run(&args);
+            assert_eq!(code, EXIT_SUCCESS);
+            let json = Json::parse(out.trim_end()).unwrap();
+            assert!(json.get("answer").and_then(Json::as_str).is_some());
+            assert!(err.starts_with("line,mass,fuel,"));
+
+            let (code, out, err) = run(&["run", "1.1", "--input", input, "report=csv"]);
+            assert_eq!(code, EXIT_SUCCESS);
+            assert!(out.starts_with("line,mass,fuel,"));
+            assert!(out.contains("\nresult: "));
+            assert_eq!(err, "");
+        }
+
+        #[test]
+        fn it_prints_only_the_answer() {
+            assert_eq!(
+                run(&["run", "2.1", "--format", "plain"]),
+                (EXIT_SUCCESS, "4023471\n".to_string(), String::new())
+            );
+            let (code, out, _) = run(&["all", "--format", "plain"]);
+            assert_eq!(code, EXIT_SUCCESS);
+            assert!(out.starts_with("1.1 3210097\n1.2 4812287\n"), "{}", out);
+        }
+
+        #[test]
+        fn it_prints_json() {
+            let (code, out, err) = run(&["run", "3.1", "--format", "json"]);
+            assert_eq!((code, err.as_str()), (EXIT_SUCCESS, ""));
+            let json = Json::parse(&out).unwrap();
+            assert_eq!(json.get("riddle").and_then(Json::as_str), Some("3.1"));
+            assert_eq!(json.get("answer").and_then(Json::as_str), Some("4981"));
+            assert_eq!(json.get("error"), Some(&Json::Null));
+            let timings = json.get("timings").unwrap();
+            assert!(timings.get("parse_ns").and_then(Json::as_i64).is_some());
+            assert!(timings.get("total_ns").and_then(Json::as_i64).is_some());
+
+            let (code, out, _) = run(&["all", "--format", "json", "--jobs", "2"]);
+            assert_eq!(code, EXIT_SUCCESS);
+            let json = Json::parse(&out).unwrap();
+            let riddles = json.get("riddles").and_then(Json::as_array).unwrap();
+            assert_eq!(riddles.len(), 6);
+            assert_eq!(
+                riddles[5].get("answer").and_then(Json::as_str),
+                Some("164012")
+            );
+            // Each day parses its input once, for the part that came first.
+            let parsed = |i: usize| {
+                let timings = riddles[i].get("timings").unwrap();
+                timings.get("parse_ns").and_then(Json::as_i64).is_some()
+            };
+            assert_eq!([parsed(2), parsed(3)].iter().filter(|p| **p).count(), 1);
+            assert_eq!([parsed(4), parsed(5)].iter().filter(|p| **p).count(), 1);
+        }
+
+        #[test]
+        fn it_prints_json_lines() {
+            let (code, out, _) = run(&["all", "--format", "jsonl"]);
+            assert_eq!(code, EXIT_SUCCESS);
+            let records: Vec<Json> = out.lines().map(|line| Json::parse(line).unwrap()).collect();
+            assert_eq!(records.len(), 6);
+            assert_eq!(records[2].get("riddle").and_then(Json::as_str), Some("2.1"));
+        }
+
+        #[test]
+        fn it_prints_structured_errors() {
+            let (code, out, err) = run(&["run", "9.1", "--format", "jsonl"]);
+            assert_eq!((code, err.as_str()), (EXIT_UNKNOWN_RIDDLE, ""));
+            let json = Json::parse(&out).unwrap();
+            assert_eq!(json.get("answer"), Some(&Json::Null));
+            assert_eq!(json.get("timings"), Some(&Json::Null));
+            let error = json.get("error").unwrap();
+            assert_eq!(
+                error.get("kind").and_then(Json::as_str),
+                Some("unknown-riddle")
+            );
+            assert_eq!(
+                error.get("message").and_then(Json::as_str),
+                Some("unknown riddle '9.1'")
+            );
+
+            let args = [
+                "run",
+                "1.1",
+                "--input",
+                "./does/not/exist",
+                "--format",
+                "json",
+            ];
+            let (code, out, _) = run(&args);
+            assert_eq!(code, EXIT_IO);
+            let error = Json::parse(&out).unwrap().get("error").unwrap().clone();
+            assert_eq!(error.get("kind").and_then(Json::as_str), Some("io"));
+        }
+
+        #[test]
+        fn it_lists_the_riddles() {
+            let (code, out, _) = run(&["list"]);
+            assert_eq!(code, EXIT_SUCCESS);
+            assert_eq!(out.lines().count(), 6);
+            assert!(out.starts_with("1.1  The Tyranny of the Rocket Equation\n"));
+        }
+
+        #[test]
+        fn it_lists_the_riddles_in_the_help() {
+            let (code, out, _) = run(&["--help"]);
+            assert_eq!(code, EXIT_SUCCESS);
+            assert!(out.contains("3.2  Crossed Wires (./data/input/3.txt)"));
+
+            let (_, out, _) = run(&["--profile", "bob", "--data-dir", "/srv/aoc", "help"]);
+            assert!(out.contains("3.2  Crossed Wires (/srv/aoc/input/bob/3.txt)"));
+        }
+
+        #[test]
+        fn it_reads_the_inputs_of_a_profile() {
+            let data_dir = TempDir::new("cli-profile-test");
+            let inputs = data_dir.join("input").join("carol");
+            fs::create_dir_all(&inputs).unwrap();
+            fs::write(inputs.join("3.txt"), "R8,U5,L5,D3\nU7,R6,D4,L4\n").unwrap();
+            let data_dir_arg = data_dir.path().to_str().unwrap();
+
+            let args = [
+                "run",
+                "3.1",
+                "--data-dir",
+                data_dir_arg,
+                "--profile",
+                "carol",
+            ];
+            let (code, out, _) = run(&args);
+            assert_eq!((code, out.as_str()), (EXIT_SUCCESS, "result: 6\n"));
+
+            let args = [
+                "run",
+                "3.1",
+                "--data-dir",
+                data_dir_arg,
+                "--profile",
+                "dave",
+            ];
+            let (code, _, err) = run(&args);
+            assert_eq!(code, EXIT_MISSING_INPUT);
+            let missing = data_dir.join("input").join("dave").join("3.txt");
+            assert!(err.starts_with(&format!(
+                "error: 3.1: missing input {}, ",
+                missing.to_str().unwrap()
+            )));
+        }
+
+        #[test]
+        fn it_reads_the_environment() {
+            let env = |name: &str| match name {
+                "AOC19_DATA_DIR" => Some(String::new()),
+                "AOC19_PROFILE" => Some("nobody".to_string()),
+                _ => None,
+            };
+            let (code, _, err) = run_with_env(&["run", "1.1"], "", &env);
+            assert_eq!(code, EXIT_MISSING_INPUT);
+            let missing = "error: 1.1: missing input ./data/input/nobody/1.txt, ";
+            assert!(err.starts_with(missing), "{}", err);
+        }
+
+        #[test]
+        fn it_reports_invalid_configurations() {
+            let (code, _, err) = run(&["list", "--profile", "../bob"]);
+            assert_eq!(code, EXIT_FAILURE);
+            assert!(err.starts_with("error: invalid profile '../bob'"));
+
+            let (code, _, err) = run(&["list", "--config", "./does/not/exist"]);
+            assert_eq!(code, EXIT_IO);
+            assert!(err.starts_with("error: cannot read the config in ./does/not/exist"));
+
+            let (code, _, err) = run(&["list", "--data-dir"]);
+            assert_eq!(code, EXIT_USAGE);
+            assert!(err.starts_with("error: --data-dir expects a value"));
+        }
+
+        #[test]
+        fn it_lists_the_options_of_a_riddle() {
+            let (code, out, _) = run(&["help", "2.2"]);
+            assert_eq!(code, EXIT_SUCCESS);
+            assert!(out.starts_with("2.2  1202 Program Alarm\n\noptions:\n  target=<n>  "));
+        }
+
+        #[test]
+        fn it_prints_the_usage_of_invalid_options() {
+            let (code, _, err) = run(&["run", "3.1", "metric=taxicab"]);
+            assert_eq!(code, EXIT_INVALID_ARGUMENTS);
+            assert!(err.contains("metric expects manhattan|chebyshev|euclidean-squared|wire-steps"));
+            assert!(err.contains("\noptions:\n  metric="), "{}", err);
+        }
+
+        #[test]
+        fn it_summarizes_all_riddles() {
+            let (code, out, err) = run(&["all", "--jobs", "3"]);
+            assert_eq!((code, err.as_str()), (EXIT_SUCCESS, ""));
+            let lines: Vec<&str> = out.lines().collect();
+            assert_eq!(lines.len(), 8);
+            assert!(lines[0].starts_with("day  part  answer   "));
+            assert!(lines[1].starts_with("  1     1  3210097  "));
+            assert!(lines[6].starts_with("  3     2  164012   ") && lines[6].ends_with("  ok"));
+            assert!(lines[7].starts_with("6 riddles, 0 failed, "));
+        }
+
+        #[test]
+        fn it_verifies_given_answers() {
+            assert_eq!(run(&["verify", "3.1", "4981"]).0, EXIT_SUCCESS);
+            let (code, out, _) = run(&["verify", "3.1", "42"]);
+            assert_eq!(code, EXIT_WRONG_ANSWER);
+            assert_eq!(
+                out,
+                "3.1: wrong answer\n- 42\n+ 4981\n1 riddles, 1 wrong, 0 without an answer, 0 failed\n"
+            );
+
+            // A custom input is not checked against the answers of the data
+            // directory.
+            let (code, _, err) = run(&["verify", "1.1", "--input", "small.txt"]);
+            assert_eq!(code, EXIT_USAGE);
+            assert!(err.starts_with("error: --input needs the answer to check or --answers"));
+            let input = "./data/input/1.txt";
+            let (code, out, _) = run(&["verify", "1.1", "3210097", "--input", input]);
+            assert_eq!(code, EXIT_SUCCESS, "{}", out);
+        }
+
+        #[test]
+        fn it_verifies_the_recorded_answers() {
+            let (code, out, err) = run(&["verify", "--jobs", "3"]);
+            assert_eq!((code, err.as_str()), (EXIT_SUCCESS, ""));
+            assert!(out.starts_with("1.1: ok\n1.2: ok\n"));
+            assert!(out.ends_with("6 riddles, 0 wrong, 0 without an answer, 0 failed\n"));
+
+            let (code, _, err) = run(&["verify", "--answers", "./does/not/exist"]);
+            assert_eq!(code, EXIT_IO);
+            assert!(err.contains("cannot read the answers in ./does/not/exist"));
+        }
+
+        #[test]
+        fn it_records_answers() {
+            let dir = TempDir::new("record-answers-test");
+            let path = dir.join("answers.txt");
+            let answers = path.to_str().unwrap();
+            fs::write(answers, "# mine\n2.1 = 1202\n").unwrap();
+
+            let (code, out, _) = run_with_stdin(&["verify", "2.1", "--answers", answers], "");
+            assert_eq!(code, EXIT_WRONG_ANSWER);
+            assert!(out.contains("- 1202\n+ 4023471\n"));
+
+            let args = ["verify", "2.2", "--answers", answers, "--record"];
+            let (code, out, _) = run_with_stdin(&args, "n\n");
+            assert_eq!(code, EXIT_NO_ANSWER);
+            assert!(out.contains("record 2.2 = 8051? [y/N] "));
+
+            let args = ["verify", "2.1", "--answers", answers, "--record"];
+            let (code, out, _) = run_with_stdin(&args, "y\n");
+            assert_eq!(code, EXIT_SUCCESS);
+            assert!(out.contains("recorded 1 answers in "));
+            assert_eq!(
+                fs::read_to_string(answers).unwrap(),
+                "# mine\n2.1 = 4023471\n"
+            );
+        }
+
+        #[test]
+        fn it_keeps_answers_files_it_cannot_parse() {
+            let dir = TempDir::new("record-invalid-answers-test");
+            let path = dir.join("answers.txt");
+            let answers = path.to_str().unwrap();
+            let text = "1.1 = 3210097\n1.2 = 4812287\n1.2 = 4812287\n2.2 = 8051\n";
+            fs::write(answers, text).unwrap();
+
+            let args = ["verify", "2.1", "--answers", answers, "--record"];
+            let (code, out, err) = run_with_stdin(&args, "y\n");
+            assert_eq!(code, EXIT_FAILURE);
+            assert!(!out.contains("record 2.1"));
+            assert!(err.contains(":3: 1.2 is recorded more than once"));
+            assert_eq!(fs::read_to_string(answers).unwrap(), text);
+        }
+
+        #[test]
+        fn it_benchmarks_riddles() {
+            let (code, out, err) = run(&["bench", "2.1", "--runs", "2"]);
+            assert_eq!((code, err.as_str()), (EXIT_SUCCESS, ""));
+            let lines: Vec<&str> = out.lines().collect();
+            assert_eq!(lines.len(), 2);
+            assert!(lines[0].starts_with("riddle  runs  "), "{}", out);
+            assert!(lines[0].ends_with("  solve"), "{}", out);
+            assert!(lines[1].starts_with("2.1        2  "), "{}", out);
+        }
+
+        #[test]
+        fn it_compares_benchmarks_with_a_baseline() {
+            let dir = TempDir::new("bench-baseline-test");
+            let path = dir.join("baseline.json");
+            let results = path.to_str().unwrap();
+
+            let args = ["bench", "1.1", "--runs", "1", "--output", results];
+            let (code, out, _) = run(&args);
+            assert_eq!(code, EXIT_SUCCESS);
+            assert!(out.ends_with(&format!("wrote the results to {}\n", results)));
+            let args = ["bench", "1.1", "--runs", "1", "--baseline", results];
+            let args = [&args[..], &["--threshold", "1000000"]].concat();
+            assert_eq!(run(&args).0, EXIT_SUCCESS);
+
+            let stats = "{\"min_ns\":1,\"median_ns\":1,\"mean_ns\":1,\"stddev_ns\":0}";
+            let baseline = format!(
+                "{{\"riddles\":[{{\"riddle\":\"1.1\",\"runs\":1,\"total\":{0},\"parse\":null,\"solve\":{0}}}]}}",
+                stats
+            );
+            fs::write(results, baseline).unwrap();
+            let (code, out, _) = run(&["bench", "--runs", "1", "--baseline", results]);
+            assert_eq!(code, EXIT_REGRESSION);
+            assert_eq!(out.lines().count(), 8, "{}", out);
+            assert!(out.contains("\n1.1: regression, median "), "{}", out);
+
+            fs::write(results, "[]").unwrap();
+            let (code, _, err) = run(&["bench", "1.1", "--baseline", results]);
+            assert_eq!(code, EXIT_FAILURE);
+            assert!(err.contains("expected benchmark results of riddles"));
+        }
+
+        #[test]
+        fn it_exits_with_the_code_of_the_error() {
+            let (code, _, err) = run(&["run", "9.1"]);
+            assert_eq!(code, EXIT_UNKNOWN_RIDDLE);
+            assert!(err.starts_with("error: 9.1: "));
+            assert_eq!(
+                run(&["run", "1.1", "--input", "./does/not/exist"]).0,
+                EXIT_IO
+            );
+
+            let (code, _, err) = run(&["frobnicate"]);
+            assert_eq!(code, EXIT_USAGE);
+            assert!(err.contains("unknown command 'frobnicate'"));
+            assert!(err.contains("usage: aoc19"));
+        }
+
+        #[test]
+        fn it_has_distinct_exit_codes() {
+            let errors = [
+                RiddleError::UnknownRiddle("9.9".to_string()),
+                RiddleError::IO(std::io::Error::other("io")),
+                RiddleError::ArgumentsCount(1, 2),
+                RiddleError::InvalidArguments {
+                    message: "invalid".to_string(),
+                    usage: String::new(),
+                },
+                RiddleError::Generic("generic".to_string()),
+                RiddleError::NoSolutionFound,
+                RiddleError::MissingInput("./data/input/9.txt".to_string()),
+            ];
+            let mut codes: Vec<i32> = errors.iter().map(exit_code).collect();
+            codes.push(EXIT_USAGE);
+            codes.sort_unstable();
+            codes.dedup();
+            assert_eq!(codes.len(), errors.len() + 1);
+            assert!(!codes.contains(&EXIT_SUCCESS));
+
+            let parse = RiddleError::Parse(Box::new(RiddleError::IO(std::io::Error::other("io"))));
+            assert_eq!(exit_code(&parse), EXIT_IO);
+        }
+    }
+
+    mod write_summary_tests {
+        use super::super::super::riddle::advent2::Advent2Riddle1;
+        use super::super::super::riddle::args::NO_ARGS;
+        use super::super::super::riddle::riddlecontainer::RiddleContainer;
+        use super::super::super::riddle::RiddleEntry;
+        use super::super::{write_summary, EXIT_MISSING_INPUT};
+
+        static MISSING_INPUT: RiddleEntry = RiddleEntry {
+            day: 2,
+            part: 1,
+            name: "Missing input",
+            default_input: "does-not-exist.txt",
+            args: &NO_ARGS,
+            build: |input, shared| Box::new(Advent2Riddle1::build(input, shared)),
+        };
+
+        #[test]
+        fn it_reports_failed_riddles() {
+            let riddles = RiddleContainer::with_entries(Some(&MISSING_INPUT));
+            let mut out = Vec::new();
+            let mut err = Vec::new();
+            let code = write_summary(&mut out, &mut err, &riddles.solve_all(2)).unwrap();
+            let out = String::from_utf8(out).unwrap();
+            let err = String::from_utf8(err).unwrap();
+
+            assert_eq!(code, EXIT_MISSING_INPUT);
+            assert!(out.lines().nth(1).unwrap().ends_with("  failed"));
+            assert!(out.contains("1 riddles, 1 failed, "));
+            assert!(err.starts_with("error: 2.1: missing input ./data/input/does-not-exist.txt, "));
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::convert::TryFrom;
+
+use super::Point;
+
+/// The smallest axis-aligned rectangle containing a set of points. Both
+/// corners are inclusive.
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub struct BoundingBox {
+    pub min: Point<i64>,
+    pub max: Point<i64>,
+}
+
+impl BoundingBox {
+    pub fn from_points<I: IntoIterator<Item = Point<i64>>>(points: I) -> Option<BoundingBox> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let mut bounding_box = BoundingBox {
+            min: first,
+            max: first,
+        };
+        for point in points {
+            bounding_box.extend(point);
+        }
+        Some(bounding_box)
+    }
+
+    pub fn extend(&mut self, point: Point<i64>) {
+        self.min.x = self.min.x.min(point.x);
+        self.min.y = self.min.y.min(point.y);
+        self.max.x = self.max.x.max(point.x);
+        self.max.y = self.max.y.max(point.y);
+    }
+
+    pub fn contains(&self, point: &Point<i64>) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x) && (self.min.y..=self.max.y).contains(&point.y)
+    }
+
+    /// The number of columns, or `None` if there are more than `i64::MAX`.
+    pub fn width(&self) -> Option<usize> {
+        span(self.min.x, self.max.x)
+    }
+
+    /// The number of rows, or `None` if there are more than `i64::MAX`.
+    pub fn height(&self) -> Option<usize> {
+        span(self.min.y, self.max.y)
+    }
+
+    /// All points inside the box, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point<i64>> {
+        let BoundingBox { min, max } = *self;
+        (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| Point::new(x, y)))
+    }
+}
+
+fn span(min: i64, max: i64) -> Option<usize> {
+    let span = max.checked_sub(min)?.checked_add(1)?;
+    usize::try_from(span).ok()
+}
+
+#[cfg(test)]
+mod bounding_box_tests {
+    use super::super::Point;
+    use super::BoundingBox;
+
+    #[test]
+    fn it_works_as_expected() {
+        let points = vec![Point::new(1, -2), Point::new(-3, 4), Point::new(0, 0)];
+        let bounding_box = BoundingBox::from_points(points).unwrap();
+
+        assert_eq!(bounding_box.min, Point::new(-3, -2));
+        assert_eq!(bounding_box.max, Point::new(1, 4));
+        assert_eq!(bounding_box.width(), Some(5));
+        assert_eq!(bounding_box.height(), Some(7));
+        assert_eq!(bounding_box.points().count(), 35);
+        assert!(bounding_box.contains(&Point::new(-3, 4)));
+        assert!(!bounding_box.contains(&Point::new(2, 0)));
+    }
+
+    #[test]
+    fn huge_boxes_have_no_size() {
+        let points = vec![Point::new(i64::MIN, 0), Point::new(i64::MAX, -1)];
+        let bounding_box = BoundingBox::from_points(points).unwrap();
+        assert_eq!(bounding_box.width(), None);
+        assert_eq!(bounding_box.height(), Some(2));
+
+        let points = vec![Point::new(0, -1), Point::new(i64::MAX, 0)];
+        let bounding_box = BoundingBox::from_points(points).unwrap();
+        assert_eq!(bounding_box.width(), None);
+    }
+
+    #[test]
+    fn it_is_empty_without_points() {
+        assert_eq!(BoundingBox::from_points(vec![]), None);
+    }
+}
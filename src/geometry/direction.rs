@@ -0,0 +1,105 @@
+use super::Point;
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    /// All directions, clockwise starting from north.
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    /// The four directions that do not move diagonally, clockwise starting
+    /// from north.
+    pub const CARDINAL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    pub fn delta(self) -> Point<i64> {
+        match self {
+            Direction::North => Point::new(0, 1),
+            Direction::NorthEast => Point::new(1, 1),
+            Direction::East => Point::new(1, 0),
+            Direction::SouthEast => Point::new(1, -1),
+            Direction::South => Point::new(0, -1),
+            Direction::SouthWest => Point::new(-1, -1),
+            Direction::West => Point::new(-1, 0),
+            Direction::NorthWest => Point::new(-1, 1),
+        }
+    }
+
+    pub fn is_diagonal(self) -> bool {
+        self.index() % 2 == 1
+    }
+
+    /// Turns by 90 degrees counter-clockwise.
+    pub fn turn_left(self) -> Direction {
+        self.rotate(6)
+    }
+
+    /// Turns by 90 degrees clockwise.
+    pub fn turn_right(self) -> Direction {
+        self.rotate(2)
+    }
+
+    pub fn reverse(self) -> Direction {
+        self.rotate(4)
+    }
+
+    /// Rotates clockwise by the given number of 45 degree steps.
+    fn rotate(self, eighths: usize) -> Direction {
+        Direction::ALL[(self.index() + eighths) % 8]
+    }
+
+    fn index(self) -> usize {
+        Direction::ALL.iter().position(|&d| d == self).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod direction_tests {
+    mod turn_tests {
+        use super::super::Direction;
+
+        #[test]
+        fn it_works_as_expected() {
+            assert_eq!(Direction::North.turn_left(), Direction::West);
+            assert_eq!(Direction::North.turn_right(), Direction::East);
+            assert_eq!(Direction::SouthWest.turn_right(), Direction::NorthWest);
+            assert_eq!(Direction::NorthEast.reverse(), Direction::SouthWest);
+            for d in Direction::ALL.iter() {
+                assert_eq!(d.turn_left().turn_right(), *d);
+                assert_eq!(
+                    d.delta() + d.reverse().delta(),
+                    super::super::Point::new(0, 0)
+                );
+            }
+        }
+
+        #[test]
+        fn diagonals_are_detected() {
+            let diagonals: Vec<_> = Direction::ALL.iter().filter(|d| d.is_diagonal()).collect();
+            assert_eq!(diagonals.len(), 4);
+            assert!(Direction::CARDINAL.iter().all(|d| !d.is_diagonal()));
+        }
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use super::{BoundingBox, Point};
+
+/// A rectangular grid storing a value for every cell.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+/// The most cells `SparseGrid::to_grid` fills, so that drawing a few points
+/// far apart cannot exhaust memory.
+pub const MAX_GRID_CELLS: usize = 100_000_000;
+
+impl<T: Clone> Grid<T> {
+    /// A grid with every cell set to `value`. Returns `None` if the number of
+    /// cells does not fit into a `usize`.
+    pub fn new(width: usize, height: usize, value: T) -> Option<Grid<T>> {
+        let cells = width.checked_mul(height)?;
+        Some(Grid {
+            width,
+            height,
+            cells: vec![value; cells],
+        })
+    }
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid from its rows. Returns `None` if the rows differ in
+    /// length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Grid<T>> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |row| row.len());
+        if rows.iter().any(|row| row.len() != width) {
+            return None;
+        }
+        let cells = rows.into_iter().flatten().collect();
+        Some(Grid {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, point: &Point<i64>) -> bool {
+        point.x >= 0
+            && point.y >= 0
+            && (point.x as usize) < self.width
+            && (point.y as usize) < self.height
+    }
+
+    pub fn get(&self, point: &Point<i64>) -> Option<&T> {
+        self.cell_index(point).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, point: &Point<i64>) -> Option<&mut T> {
+        self.cell_index(point).map(move |i| &mut self.cells[i])
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1)).take(self.height)
+    }
+
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        if self.cells.is_empty() {
+            return None;
+        }
+        Some(BoundingBox {
+            min: Point::new(0, 0),
+            max: Point::new(self.width as i64 - 1, self.height as i64 - 1),
+        })
+    }
+
+    /// All points of the grid together with their values, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point<i64>, &T)> {
+        let width = self.width;
+        self.cells.iter().enumerate().map(move |(i, value)| {
+            let point = Point::new((i % width) as i64, (i / width) as i64);
+            (point, value)
+        })
+    }
+
+    /// The 4-connected neighbours of a point that lie inside the grid.
+    pub fn neighbours4<'a>(&'a self, point: &Point<i64>) -> impl Iterator<Item = Point<i64>> + 'a {
+        point.neighbours4().filter(move |p| self.contains(p))
+    }
+
+    /// The 8-connected neighbours of a point that lie inside the grid.
+    pub fn neighbours8<'a>(&'a self, point: &Point<i64>) -> impl Iterator<Item = Point<i64>> + 'a {
+        point.neighbours8().filter(move |p| self.contains(p))
+    }
+
+    fn cell_index(&self, point: &Point<i64>) -> Option<usize> {
+        if self.contains(point) {
+            Some(point.y as usize * self.width + point.x as usize)
+        } else {
+            None
+        }
+    }
+}
+
+impl<T> Index<Point<i64>> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point<i64>) -> &T {
+        self.get(&point).expect("point outside of grid")
+    }
+}
+
+impl<T> IndexMut<Point<i64>> for Grid<T> {
+    fn index_mut(&mut self, point: Point<i64>) -> &mut T {
+        self.get_mut(&point).expect("point outside of grid")
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, row) in self.rows().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A grid that only stores the cells that have been set, without any bounds.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point<i64>, T>,
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> SparseGrid<T> {
+        SparseGrid {
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn insert(&mut self, point: Point<i64>, value: T) -> Option<T> {
+        self.cells.insert(point, value)
+    }
+
+    pub fn remove(&mut self, point: &Point<i64>) -> Option<T> {
+        self.cells.remove(point)
+    }
+
+    pub fn get(&self, point: &Point<i64>) -> Option<&T> {
+        self.cells.get(point)
+    }
+
+    pub fn get_mut(&mut self, point: &Point<i64>) -> Option<&mut T> {
+        self.cells.get_mut(point)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Point<i64>, &T)> {
+        self.cells.iter()
+    }
+
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        BoundingBox::from_points(self.cells.keys().cloned())
+    }
+}
+
+impl<T: Clone> SparseGrid<T> {
+    /// Copies the cells into a dense grid spanning the bounding box, filling
+    /// the unset cells with `empty`. Points have their y axis up and grids
+    /// count rows from the top, so the top-left cell is the point with the
+    /// smallest `x` and the largest `y`. Returns `None` if the bounding box
+    /// has more than `MAX_GRID_CELLS` cells.
+    pub fn to_grid(&self, empty: T) -> Option<Grid<T>> {
+        let bounding_box = match self.bounding_box() {
+            Some(bounding_box) => bounding_box,
+            None => return Grid::new(0, 0, empty),
+        };
+        let width = bounding_box.width()?;
+        let height = bounding_box.height()?;
+        if width.checked_mul(height)? > MAX_GRID_CELLS {
+            return None;
+        }
+        let mut grid = Grid::new(width, height, empty)?;
+        let BoundingBox { min, max } = bounding_box;
+        for (point, value) in self.cells.iter() {
+            grid[Point::new(point.x - min.x, max.y - point.y)] = value.clone();
+        }
+        Some(grid)
+    }
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod grid_tests {
+    mod dense_grid_tests {
+        use super::super::super::Point;
+        use super::super::Grid;
+
+        fn example() -> Grid<char> {
+            let rows = ["#..", ".#.", "..#", "##."];
+            Grid::from_rows(rows.iter().map(|r| r.chars().collect()).collect()).unwrap()
+        }
+
+        #[test]
+        fn it_works_as_expected() {
+            let mut grid = example();
+            assert_eq!(grid.width(), 3);
+            assert_eq!(grid.height(), 4);
+            assert_eq!(grid.get(&Point::new(1, 1)), Some(&'#'));
+            assert_eq!(grid.get(&Point::new(2, 1)), Some(&'.'));
+            assert_eq!(grid.get(&Point::new(3, 0)), None);
+            assert_eq!(grid.get(&Point::new(0, -1)), None);
+
+            grid[Point::new(2, 1)] = '#';
+            assert_eq!(grid.to_string(), "#..\n.##\n..#\n##.");
+            assert_eq!(grid.iter().filter(|(_, c)| **c == '#').count(), 6);
+        }
+
+        #[test]
+        fn it_rejects_ragged_rows() {
+            assert_eq!(Grid::from_rows(vec![vec![1, 2], vec![3]]), None);
+        }
+
+        #[test]
+        fn it_rejects_sizes_that_overflow() {
+            assert_eq!(Grid::new(usize::MAX, 2, 0), None);
+            assert_eq!(Grid::new(3, 2, 0).unwrap().to_string(), "000\n000");
+        }
+
+        #[test]
+        fn neighbours_stay_inside() {
+            let grid = example();
+            assert_eq!(grid.neighbours4(&Point::new(0, 0)).count(), 2);
+            assert_eq!(grid.neighbours8(&Point::new(0, 0)).count(), 3);
+            assert_eq!(grid.neighbours8(&Point::new(1, 1)).count(), 8);
+        }
+    }
+
+    mod sparse_grid_tests {
+        use super::super::super::{BoundingBox, Point};
+        use super::super::{SparseGrid, MAX_GRID_CELLS};
+
+        #[test]
+        fn it_works_as_expected() {
+            let mut grid = SparseGrid::new();
+            assert_eq!(grid.bounding_box(), None);
+
+            grid.insert(Point::new(-1, 2), '#');
+            grid.insert(Point::new(1, 0), '#');
+            assert_eq!(grid.len(), 2);
+            assert_eq!(
+                grid.bounding_box(),
+                Some(BoundingBox {
+                    min: Point::new(-1, 0),
+                    max: Point::new(1, 2),
+                })
+            );
+            assert_eq!(grid.to_grid('.').unwrap().to_string(), "#..\n...\n..#");
+        }
+
+        #[test]
+        fn it_draws_north_at_the_top() {
+            // An L: a column going north with a foot pointing east.
+            let mut grid = SparseGrid::new();
+            for point in &[(0, 0), (0, 1), (0, 2), (1, 0)] {
+                grid.insert(Point::new(point.0, point.1), '#');
+            }
+            assert_eq!(grid.to_grid('.').unwrap().to_string(), "#.\n#.\n##");
+        }
+
+        #[test]
+        fn it_only_draws_grids_that_fit() {
+            let mut grid = SparseGrid::new();
+            assert_eq!(grid.to_grid('.').unwrap().width(), 0);
+            grid.insert(Point::new(i64::MIN, 0), '#');
+            grid.insert(Point::new(i64::MAX, 0), '#');
+            assert_eq!(grid.to_grid('.'), None);
+        }
+
+        #[test]
+        fn it_caps_the_cells_it_draws() {
+            let mut grid = SparseGrid::new();
+            grid.insert(Point::new(0, 0), '#');
+            grid.insert(Point::new(1 << 31, 1), '#');
+            assert_eq!(grid.to_grid('.'), None);
+
+            let mut grid = SparseGrid::new();
+            grid.insert(Point::new(0, 0), '#');
+            grid.insert(Point::new(MAX_GRID_CELLS as i64, 0), '#');
+            assert_eq!(grid.to_grid('.'), None);
+        }
+    }
+}
@@ -0,0 +1,16 @@
+//! Two-dimensional geometry shared by the riddles: points, directions, grids
+//! and bounding boxes.
+//!
+//! Points use a y axis that points up, so `Direction::North` increases `y`.
+//! Grids index their cells with `x` as column and `y` as row, counted from
+//! the top-left corner.
+
+mod bounding_box;
+mod direction;
+mod grid;
+mod point;
+
+pub use bounding_box::BoundingBox;
+pub use direction::Direction;
+pub use grid::{Grid, SparseGrid, MAX_GRID_CELLS};
+pub use point::{ParsePointError, Point};
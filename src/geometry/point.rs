@@ -0,0 +1,243 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str;
+
+use super::Direction;
+
+#[derive(PartialEq, Eq, Debug, Hash, Clone, Copy, Default, PartialOrd, Ord)]
+pub struct Point<T = i64> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Point<T> {
+    pub fn new(x: T, y: T) -> Point<T> {
+        Point { x, y }
+    }
+}
+
+impl<T: Add<Output = T>> Add for Point<T> {
+    type Output = Point<T>;
+
+    fn add(self, other: Point<T>) -> Point<T> {
+        Point {
+            x: self.x + other.x,
+            y: self.y + other.y,
+        }
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point<T> {
+    type Output = Point<T>;
+
+    fn sub(self, other: Point<T>) -> Point<T> {
+        Point {
+            x: self.x - other.x,
+            y: self.y - other.y,
+        }
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Point<T> {
+    type Output = Point<T>;
+
+    fn neg(self) -> Point<T> {
+        Point {
+            x: -self.x,
+            y: -self.y,
+        }
+    }
+}
+
+impl<T: Mul<Output = T> + Copy> Mul<T> for Point<T> {
+    type Output = Point<T>;
+
+    fn mul(self, factor: T) -> Point<T> {
+        Point {
+            x: self.x * factor,
+            y: self.y * factor,
+        }
+    }
+}
+
+impl<T: AddAssign> AddAssign for Point<T> {
+    fn add_assign(&mut self, other: Point<T>) {
+        self.x += other.x;
+        self.y += other.y;
+    }
+}
+
+impl<T: SubAssign> SubAssign for Point<T> {
+    fn sub_assign(&mut self, other: Point<T>) {
+        self.x -= other.x;
+        self.y -= other.y;
+    }
+}
+
+impl Point<i64> {
+    pub fn origin() -> Point<i64> {
+        Point { x: 0, y: 0 }
+    }
+
+    pub fn offset_from(&self, other: &Point<i64>) -> Point<i64> {
+        *self - *other
+    }
+
+    /// The Manhattan norm, or `None` if it does not fit into a `usize`.
+    pub fn manhattan_norm(&self) -> Option<usize> {
+        let (x, y) = self.unsigned_coordinates()?;
+        x.checked_add(y)
+    }
+
+    pub fn chebyshev_norm(&self) -> usize {
+        self.x.unsigned_abs().max(self.y.unsigned_abs()) as usize
+    }
+
+    /// The squared Euclidean norm, or `None` if it does not fit into a
+    /// `usize`.
+    pub fn euclidean_norm_squared(&self) -> Option<usize> {
+        let (x, y) = self.unsigned_coordinates()?;
+        x.checked_mul(x)?.checked_add(y.checked_mul(y)?)
+    }
+
+    fn unsigned_coordinates(&self) -> Option<(usize, usize)> {
+        let x = usize::try_from(self.x.unsigned_abs()).ok()?;
+        let y = usize::try_from(self.y.unsigned_abs()).ok()?;
+        Some((x, y))
+    }
+
+    pub fn step(&self, direction: Direction) -> Point<i64> {
+        *self + direction.delta()
+    }
+
+    /// The four points sharing an edge with this one.
+    pub fn neighbours4(&self) -> impl Iterator<Item = Point<i64>> {
+        let point = *self;
+        Direction::CARDINAL.iter().map(move |d| point.step(*d))
+    }
+
+    /// The eight points sharing an edge or a corner with this one.
+    pub fn neighbours8(&self) -> impl Iterator<Item = Point<i64>> {
+        let point = *self;
+        Direction::ALL.iter().map(move |d| point.step(*d))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point<T> {
+    /// Writes points as `x,y`, the way they are parsed.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParsePointError(pub String);
+
+impl<T: str::FromStr> str::FromStr for Point<T> {
+    type Err = ParsePointError;
+
+    /// Parses points written as `x,y`.
+    fn from_str(s: &str) -> Result<Self, ParsePointError> {
+        let mut coordinates = s.split(',').map(|c| c.trim().parse::<T>());
+        match (coordinates.next(), coordinates.next(), coordinates.next()) {
+            (Some(Ok(x)), Some(Ok(y)), None) => Ok(Point { x, y }),
+            _ => Err(ParsePointError(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod point_tests {
+    mod arithmetic_tests {
+        use super::super::Point;
+
+        #[test]
+        fn it_works_as_expected() {
+            let a = Point::new(3, -2);
+            let b = Point::new(-1, 5);
+
+            assert_eq!(a + b, Point::new(2, 3));
+            assert_eq!(a - b, Point::new(4, -7));
+            assert_eq!(-a, Point::new(-3, 2));
+            assert_eq!(a * 3, Point::new(9, -6));
+
+            let mut c = a;
+            c += b;
+            c -= Point::new(1, 1);
+            assert_eq!(c, Point::new(1, 2));
+        }
+
+        #[test]
+        fn it_works_for_other_coordinate_types() {
+            let a = Point::new(1.5, 2.0);
+            assert_eq!(a * 2.0, Point::new(3.0, 4.0));
+        }
+    }
+
+    mod norm_tests {
+        use super::super::Point;
+
+        #[test]
+        fn it_works_as_expected() {
+            let p = Point::new(-3, 4);
+            assert_eq!(p.manhattan_norm(), Some(7));
+            assert_eq!(p.chebyshev_norm(), 4);
+            assert_eq!(p.euclidean_norm_squared(), Some(25));
+        }
+
+        #[test]
+        fn it_detects_overflow() {
+            let p = Point::new(i64::MIN, i64::MIN);
+            assert_eq!(p.manhattan_norm(), None);
+            assert_eq!(p.euclidean_norm_squared(), None);
+            assert_eq!(p.chebyshev_norm(), 1 << 63);
+        }
+    }
+
+    mod neighbour_tests {
+        use super::super::Point;
+
+        #[test]
+        fn it_works_as_expected() {
+            let p = Point::new(2, 2);
+            let mut neighbours4: Vec<_> = p.neighbours4().collect();
+            neighbours4.sort();
+            assert_eq!(
+                neighbours4,
+                vec![
+                    Point::new(1, 2),
+                    Point::new(2, 1),
+                    Point::new(2, 3),
+                    Point::new(3, 2),
+                ]
+            );
+            assert_eq!(p.neighbours8().count(), 8);
+            assert!(p.neighbours8().all(|n| (n - p).chebyshev_norm() == 1));
+        }
+    }
+
+    mod parsing_tests {
+        use super::super::{ParsePointError, Point};
+
+        #[test]
+        fn it_works_as_expected() {
+            assert_eq!("3,-4".parse::<Point>(), Ok(Point::new(3, -4)));
+            assert_eq!(" 1 , 2 ".parse::<Point<u8>>(), Ok(Point::new(1, 2)));
+        }
+
+        #[test]
+        fn it_writes_points_the_way_they_are_parsed() {
+            let p = Point::new(-7, 12);
+            assert_eq!(p.to_string(), "-7,12");
+            assert_eq!(p.to_string().parse::<Point>(), Ok(p));
+        }
+
+        #[test]
+        fn it_complains_about_invalid_points() {
+            for s in ["3", "1,2,3", "a,b", ""] {
+                assert_eq!(s.parse::<Point>(), Err(ParsePointError(s.to_string())));
+            }
+        }
+    }
+}
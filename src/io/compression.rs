@@ -0,0 +1,165 @@
+//! Transparent decompression of input files. Compression is detected by the
+//! magic bytes at the start of a file. Decompressing needs the `compression`
+//! feature; without it, compressed files are rejected.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read};
+
+/// The compression of a file.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Detects the compression from the first bytes of a file.
+    pub fn detect(start: &[u8]) -> Option<Compression> {
+        if start.starts_with(&[0x1f, 0x8b]) {
+            Some(Compression::Gzip)
+        } else if start.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Some(Compression::Zstd)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Compression::Gzip => write!(f, "gzip"),
+            Compression::Zstd => write!(f, "zstd"),
+        }
+    }
+}
+
+/// Returns the compression of the file, if it is compressed.
+pub fn compression_of_file(filename: &str) -> Result<Option<Compression>, Error> {
+    let mut reader = BufReader::new(File::open(filename)?);
+    Ok(Compression::detect(reader.fill_buf()?))
+}
+
+/// Reads a file and decompresses it on the fly if it is compressed.
+pub struct FileReader {
+    inner: Inner,
+}
+
+enum Inner {
+    Plain(BufReader<File>),
+    #[cfg(feature = "compression")]
+    Decompressed(BufReader<Box<dyn Read + Send>>),
+}
+
+impl FileReader {
+    pub fn open(filename: &str) -> Result<FileReader, Error> {
+        let mut reader = BufReader::new(File::open(filename)?);
+        let inner = match Compression::detect(reader.fill_buf()?) {
+            None => Inner::Plain(reader),
+            Some(compression) => decompress(reader, compression)?,
+        };
+        Ok(FileReader { inner })
+    }
+}
+
+#[cfg(feature = "compression")]
+fn decompress(reader: BufReader<File>, compression: Compression) -> Result<Inner, Error> {
+    let decoder: Box<dyn Read + Send> = match compression {
+        Compression::Gzip => Box::new(flate2::bufread::MultiGzDecoder::new(reader)),
+        Compression::Zstd => Box::new(
+            ruzstd::decoding::StreamingDecoder::new(reader)
+                .map_err(|err| Error::new(ErrorKind::InvalidData, err))?,
+        ),
+    };
+    Ok(Inner::Decompressed(BufReader::new(decoder)))
+}
+
+#[cfg(not(feature = "compression"))]
+fn decompress(_: BufReader<File>, compression: Compression) -> Result<Inner, Error> {
+    Err(Error::new(
+        ErrorKind::InvalidData,
+        format!(
+            "the file is {} compressed, which needs the `compression` feature",
+            compression
+        ),
+    ))
+}
+
+impl Read for FileReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        match &mut self.inner {
+            Inner::Plain(reader) => reader.read(buf),
+            #[cfg(feature = "compression")]
+            Inner::Decompressed(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl BufRead for FileReader {
+    fn fill_buf(&mut self) -> Result<&[u8], Error> {
+        match &mut self.inner {
+            Inner::Plain(reader) => reader.fill_buf(),
+            #[cfg(feature = "compression")]
+            Inner::Decompressed(reader) => reader.fill_buf(),
+        }
+    }
+
+    fn consume(&mut self, amount: usize) {
+        match &mut self.inner {
+            Inner::Plain(reader) => reader.consume(amount),
+            #[cfg(feature = "compression")]
+            Inner::Decompressed(reader) => reader.consume(amount),
+        }
+    }
+}
+
+#[cfg(test)]
+mod compression_tests {
+    use super::super::{chunks_of_file, ints_from_file, lines_from_file};
+    use super::{compression_of_file, Compression};
+
+    const PLAIN: &str = "./data/test/io/compressed/masses.txt";
+    const GZIP: &str = "./data/test/io/compressed/masses.txt.gz";
+    const ZSTD: &str = "./data/test/io/compressed/masses.txt.zst";
+
+    #[test]
+    fn it_detects_compression() {
+        assert_eq!(compression_of_file(PLAIN).unwrap(), None);
+        assert_eq!(compression_of_file(GZIP).unwrap(), Some(Compression::Gzip));
+        assert_eq!(compression_of_file(ZSTD).unwrap(), Some(Compression::Zstd));
+        assert_eq!(Compression::detect(&[]), None);
+    }
+
+    #[test]
+    fn it_reads_plain_files() {
+        assert_eq!(ints_from_file(PLAIN).unwrap().len(), 100);
+    }
+
+    #[test]
+    fn it_does_not_split_compressed_files() {
+        assert!(chunks_of_file(GZIP, 2).is_err());
+        assert!(chunks_of_file(ZSTD, 2).is_err());
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn it_decompresses_on_the_fly() {
+        let expected = ints_from_file(PLAIN).unwrap();
+        for path in &[GZIP, ZSTD] {
+            assert_eq!(ints_from_file(path).unwrap(), expected, "{}", path);
+            assert_eq!(lines_from_file(path).unwrap().count(), expected.len());
+        }
+    }
+
+    #[cfg(not(feature = "compression"))]
+    #[test]
+    fn it_rejects_compressed_files() {
+        for path in &[GZIP, ZSTD] {
+            match lines_from_file(path) {
+                Err(err) => assert!(err.to_string().contains("`compression` feature")),
+                Ok(_) => panic!("expected {} to be rejected", path),
+            }
+        }
+    }
+}
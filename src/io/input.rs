@@ -0,0 +1,189 @@
+use std::io::{self, BufRead, Read};
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+use super::reader_from_file;
+
+/// The input of a riddle: a file, stdin, a string or any reader.
+///
+/// Stdin and readers can only be read once, so their contents are kept in
+/// memory the first time the input is opened. That way riddles can open any
+/// input more than once.
+pub struct Input {
+    source: Source,
+    contents: OnceLock<Vec<u8>>,
+    /// Held while stdin or the reader is read, so only one thread reads it.
+    reading: Mutex<()>,
+}
+
+enum Source {
+    Path(String),
+    Stdin,
+    Text(String),
+    Reader(Mutex<Option<Box<dyn Read + Send>>>),
+}
+
+impl Input {
+    pub fn path(path: &str) -> Input {
+        Input::from_source(Source::Path(path.to_string()))
+    }
+
+    pub fn stdin() -> Input {
+        Input::from_source(Source::Stdin)
+    }
+
+    pub fn text<S: Into<String>>(text: S) -> Input {
+        Input::from_source(Source::Text(text.into()))
+    }
+
+    pub fn reader<R: Read + Send + 'static>(reader: R) -> Input {
+        Input::from_source(Source::Reader(Mutex::new(Some(Box::new(reader)))))
+    }
+
+    fn from_source(source: Source) -> Input {
+        Input {
+            source,
+            contents: OnceLock::new(),
+            reading: Mutex::new(()),
+        }
+    }
+
+    /// The name of the input in messages: the path of a file, `<stdin>`,
+    /// `<text>` or `<reader>`.
+    pub fn name(&self) -> &str {
+        match &self.source {
+            Source::Path(path) => path,
+            Source::Stdin => "<stdin>",
+            Source::Text(_) => "<text>",
+            Source::Reader(_) => "<reader>",
+        }
+    }
+
+    /// The path of the input if it is a file.
+    pub fn as_path(&self) -> Option<&str> {
+        match &self.source {
+            Source::Path(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Opens the input for reading from its start.
+    pub fn open(&self) -> io::Result<Box<dyn BufRead + '_>> {
+        match &self.source {
+            Source::Path(path) => Ok(Box::new(reader_from_file(path)?)),
+            Source::Text(text) => Ok(Box::new(text.as_bytes())),
+            Source::Stdin | Source::Reader(_) => Ok(Box::new(self.contents()?)),
+        }
+    }
+
+    fn contents(&self) -> io::Result<&[u8]> {
+        if let Some(contents) = self.contents.get() {
+            return Ok(contents);
+        }
+        let _reading = self.reading.lock().unwrap_or_else(PoisonError::into_inner);
+        // Another thread may have read the input while this one waited.
+        if let Some(contents) = self.contents.get() {
+            return Ok(contents);
+        }
+        let mut contents = Vec::new();
+        match &self.source {
+            Source::Stdin => {
+                io::stdin().lock().read_to_end(&mut contents)?;
+            }
+            Source::Reader(reader) => {
+                let mut reader = reader.lock().unwrap_or_else(PoisonError::into_inner);
+                match reader.as_mut() {
+                    Some(source) => {
+                        source.read_to_end(&mut contents)?;
+                        // Only drop the reader once its contents are kept.
+                        *reader = None;
+                    }
+                    None => return Err(io::Error::other("the reader was read before")),
+                }
+            }
+            Source::Path(_) | Source::Text(_) => unreachable!("files and texts are not kept"),
+        }
+        Ok(self.contents.get_or_init(|| contents))
+    }
+}
+
+impl From<&str> for Input {
+    fn from(path: &str) -> Self {
+        Input::path(path)
+    }
+}
+
+#[cfg(test)]
+mod input_tests {
+    use super::Input;
+    use std::io::{self, BufRead, Cursor, Read};
+    use std::thread;
+
+    fn read(input: &Input) -> String {
+        let mut contents = String::new();
+        input.open().unwrap().read_to_string(&mut contents).unwrap();
+        contents
+    }
+
+    #[test]
+    fn it_reads_files() {
+        let input = Input::from("./data/test/io/lines_from_file/three_lines.txt");
+        assert_eq!(read(&input), "a\nb\nc\n");
+        assert_eq!(input.as_path(), Some(input.name()));
+    }
+
+    #[test]
+    fn it_reads_texts() {
+        let input = Input::text("1\n2");
+        assert_eq!(input.open().unwrap().lines().count(), 2);
+        assert_eq!(input.name(), "<text>");
+        assert_eq!(input.as_path(), None);
+    }
+
+    #[test]
+    fn it_reads_readers_more_than_once() {
+        let input = Input::reader(Cursor::new(b"R8,U5\nU7,R6".to_vec()));
+        assert_eq!(read(&input), "R8,U5\nU7,R6");
+        assert_eq!(read(&input), "R8,U5\nU7,R6");
+    }
+
+    #[test]
+    fn it_reads_readers_from_many_threads() {
+        let input = Input::reader(Cursor::new(b"12\n14".to_vec()));
+        thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| assert_eq!(read(&input), "12\n14"));
+            }
+        });
+    }
+
+    /// Fails the first read, then reads its text.
+    struct FailingOnce {
+        failed: bool,
+        text: Cursor<&'static [u8]>,
+    }
+
+    impl Read for FailingOnce {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if !self.failed {
+                self.failed = true;
+                return Err(io::Error::other("not yet"));
+            }
+            self.text.read(buf)
+        }
+    }
+
+    #[test]
+    fn it_keeps_readers_that_fail() {
+        let input = Input::reader(FailingOnce {
+            failed: false,
+            text: Cursor::new(b"1969"),
+        });
+        assert!(input.open().is_err());
+        assert_eq!(read(&input), "1969");
+    }
+
+    #[test]
+    fn it_reports_missing_files() {
+        assert!(Input::path("./does/not/exist").open().is_err());
+    }
+}
@@ -0,0 +1,320 @@
+//! Zero-copy reading of input files. The lines and values of a `MappedFile`
+//! are borrowed from its contents instead of being copied into a `String`
+//! each, as `lines_from_file` does.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read};
+use std::iter::Enumerate;
+use std::str::{self, FromStr, Lines};
+
+use super::parse::{Fields, ParseError, ParseErrorKind, Separator};
+use super::{Compression, FileReader};
+
+/// The contents of a file. With the `mmap` feature, files are memory-mapped;
+/// without it, and for compressed files, they are read into memory.
+pub struct MappedFile {
+    path: String,
+    contents: Contents,
+}
+
+enum Contents {
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+    Buffer(Vec<u8>),
+}
+
+impl MappedFile {
+    pub fn open(filename: &str) -> Result<MappedFile, Error> {
+        let mut reader = BufReader::new(File::open(filename)?);
+        let compressed = Compression::detect(reader.fill_buf()?).is_some();
+        let contents = if compressed {
+            let mut contents = Vec::new();
+            FileReader::open(filename)?.read_to_end(&mut contents)?;
+            Contents::Buffer(contents)
+        } else {
+            read_contents(reader)?
+        };
+        Ok(MappedFile {
+            path: filename.to_string(),
+            contents,
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match &self.contents {
+            #[cfg(feature = "mmap")]
+            Contents::Mapped(map) => map,
+            Contents::Buffer(buffer) => buffer,
+        }
+    }
+
+    /// The contents as text. Fails if they are not valid UTF-8.
+    pub fn as_str(&self) -> Result<&str, Error> {
+        str::from_utf8(self.as_bytes())
+            .map_err(|err| Error::new(ErrorKind::InvalidData, format!("{}: {}", self.path, err)))
+    }
+
+    /// The lines of the file, without their line breaks.
+    pub fn lines(&self) -> Result<Lines<'_>, Error> {
+        Ok(self.as_str()?.lines())
+    }
+
+    /// The values of the file, separated by `separator`.
+    pub fn tokens(&self, separator: Separator) -> Result<Tokens<'_>, Error> {
+        Ok(Tokens::new(self.as_str()?, separator))
+    }
+
+    /// Parses all values of the file.
+    pub fn parse<T: FromStr>(&self, separator: Separator) -> Result<Vec<T>, ParseError<T::Err>> {
+        let text = self.as_str().map_err(|err| ParseError {
+            file: self.path.clone(),
+            line: 0,
+            column: 0,
+            line_text: String::new(),
+            kind: ParseErrorKind::IO(err),
+        })?;
+        Tokens::new(text, separator)
+            .map(|token| token.parse(&self.path))
+            .collect()
+    }
+}
+
+#[cfg(feature = "mmap")]
+fn read_contents(reader: BufReader<File>) -> Result<Contents, Error> {
+    let file = reader.get_ref();
+    if file.metadata()?.len() == 0 {
+        // Empty files cannot be mapped on every platform.
+        return Ok(Contents::Buffer(Vec::new()));
+    }
+    // SAFETY: the map is only read from. Like any memory-mapped file, its
+    // contents change if another process modifies the file while it is open,
+    // which is not expected for puzzle inputs.
+    let map = unsafe { memmap2::Mmap::map(file)? };
+    Ok(Contents::Mapped(map))
+}
+
+#[cfg(not(feature = "mmap"))]
+fn read_contents(mut reader: BufReader<File>) -> Result<Contents, Error> {
+    let mut contents = Vec::new();
+    reader.read_to_end(&mut contents)?;
+    Ok(Contents::Buffer(contents))
+}
+
+/// A value of a borrowed input, with its position.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Token<'a> {
+    /// The line of the value, counted from 1.
+    pub line: usize,
+    /// The byte offset of the value in its line.
+    pub offset: usize,
+    pub text: &'a str,
+    /// The whole line, or the whole group of lines for
+    /// `Separator::BlankLine`.
+    pub line_text: &'a str,
+}
+
+impl<'a> Token<'a> {
+    /// The column of the value, counted in characters from 1.
+    pub fn column(&self) -> usize {
+        self.line_text[..self.offset].chars().count() + 1
+    }
+
+    /// Parses the value. `file` names the input in errors.
+    pub fn parse<T: FromStr>(&self, file: &str) -> Result<T, ParseError<T::Err>> {
+        self.text.parse().map_err(|error| ParseError {
+            file: file.to_string(),
+            line: self.line,
+            column: self.column(),
+            line_text: self.line_text.to_string(),
+            kind: ParseErrorKind::Value {
+                text: self.text.to_string(),
+                error,
+            },
+        })
+    }
+}
+
+/// Splits a borrowed input into its values like `Values` does, but without
+/// copying or parsing them.
+pub struct Tokens<'a> {
+    text: &'a str,
+    lines: Enumerate<Lines<'a>>,
+    separator: Separator,
+    current: Option<(usize, &'a str, Fields<'a>)>,
+}
+
+impl<'a> Tokens<'a> {
+    pub fn new(text: &'a str, separator: Separator) -> Tokens<'a> {
+        Tokens {
+            text,
+            lines: text.lines().enumerate(),
+            separator,
+            current: None,
+        }
+    }
+
+    /// The byte offset of `slice`, which is part of the input, in the input.
+    fn offset_of(&self, slice: &str) -> usize {
+        slice.as_ptr() as usize - self.text.as_ptr() as usize
+    }
+
+    fn next_group(&mut self) -> Option<Token<'a>> {
+        let (first, first_line) = self
+            .lines
+            .by_ref()
+            .find(|(_, line)| !line.trim().is_empty())?;
+        let mut last_line = first_line;
+        for (_, line) in self.lines.by_ref() {
+            if line.trim().is_empty() {
+                break;
+            }
+            last_line = line;
+        }
+        let start = self.offset_of(first_line);
+        let end = self.offset_of(last_line) + last_line.len();
+        let group = &self.text[start..end];
+        Some(Token {
+            line: first + 1,
+            offset: 0,
+            text: group,
+            line_text: group,
+        })
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        if self.separator == Separator::BlankLine {
+            return self.next_group();
+        }
+        loop {
+            if let Some((line_number, line, fields)) = &mut self.current {
+                if let Some((offset, text)) = fields.next() {
+                    return Some(Token {
+                        line: *line_number,
+                        offset,
+                        text,
+                        line_text: line,
+                    });
+                }
+            }
+            let (i, line) = self.lines.next()?;
+            self.current = Some((i + 1, line, self.separator.split(line)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod mapped_tests {
+    mod mapped_file_tests {
+        use super::super::super::{ints_from_file, lines_from_file, Separator};
+        use super::super::MappedFile;
+
+        #[test]
+        fn it_borrows_the_lines_of_a_file() {
+            let path = "./data/input/1.txt";
+            let file = MappedFile::open(path).unwrap();
+            let lines: Vec<&str> = file.lines().unwrap().collect();
+            let expected: Vec<String> =
+                lines_from_file(path).unwrap().map(Result::unwrap).collect();
+            assert_eq!(lines, expected);
+        }
+
+        #[test]
+        fn it_parses_values() {
+            let path = "./data/input/1.txt";
+            let file = MappedFile::open(path).unwrap();
+            let masses: Vec<i64> = file.parse(Separator::Newline).unwrap();
+            assert_eq!(masses, ints_from_file(path).unwrap());
+        }
+
+        #[test]
+        fn it_names_the_file_in_errors() {
+            let path = "./data/test/io/ints_from_file/no_ints.txt";
+            let err = MappedFile::open(path)
+                .unwrap()
+                .parse::<i64>(Separator::Newline)
+                .unwrap_err();
+            assert_eq!((err.file.as_str(), err.line, err.column), (path, 3, 1));
+        }
+
+        #[test]
+        fn it_reads_empty_files() {
+            let path = "./data/test/io/empty.txt";
+            let file = MappedFile::open(path).unwrap();
+            assert_eq!(file.as_bytes(), b"");
+            assert_eq!(file.lines().unwrap().count(), 0);
+        }
+
+        #[cfg(feature = "compression")]
+        #[test]
+        fn it_decompresses_files() {
+            let file = MappedFile::open("./data/test/io/compressed/masses.txt.zst").unwrap();
+            assert_eq!(file.lines().unwrap().count(), 100);
+        }
+    }
+
+    mod tokens_tests {
+        use super::super::super::{Separator, Values};
+        use super::super::{Token, Tokens};
+
+        fn texts(input: &str, separator: Separator) -> Vec<&str> {
+            Tokens::new(input, separator)
+                .map(|token| token.text)
+                .collect()
+        }
+
+        #[test]
+        fn it_yields_the_values_of_values() {
+            let inputs = [
+                "1\n -2 \r\n\n3",
+                "a,(1,2), b\nc,\n\n,d",
+                " 1\t2  3\n\n 4 ",
+                "\na\nb\n\n\nc\n",
+            ];
+            let separators = [
+                Separator::Newline,
+                Separator::Comma,
+                Separator::Whitespace,
+                Separator::BlankLine,
+            ];
+            for input in &inputs {
+                for &separator in &separators {
+                    let expected: Vec<String> = Values::new(input.as_bytes(), "<input>", separator)
+                        .map(Result::unwrap)
+                        .collect();
+                    assert_eq!(texts(input, separator), expected, "{:?}", input);
+                }
+            }
+        }
+
+        #[test]
+        fn it_keeps_the_position() {
+            let tokens: Vec<Token> = Tokens::new("1,2\nä, x", Separator::Comma).collect();
+            assert_eq!(
+                tokens[3],
+                Token {
+                    line: 2,
+                    offset: 4,
+                    text: "x",
+                    line_text: "ä, x",
+                }
+            );
+            assert_eq!(tokens[3].column(), 4);
+        }
+
+        #[test]
+        fn it_borrows_groups() {
+            let input = "a\r\nb\r\n\r\nc";
+            let tokens: Vec<Token> = Tokens::new(input, Separator::BlankLine).collect();
+            assert_eq!(tokens[0].text, "a\r\nb");
+            assert_eq!((tokens[1].line, tokens[1].text), (4, "c"));
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Error, ErrorKind, Lines, Read, Seek, SeekFrom, Take};
+use std::num::ParseIntError;
+use std::vec::Vec;
+
+mod compression;
+mod input;
+mod mapped;
+mod parse;
+
+pub use compression::{compression_of_file, Compression, FileReader};
+pub use input::Input;
+pub use mapped::{MappedFile, Token, Tokens};
+pub use parse::{
+    grid_from_file, grid_from_input, parse_file, parse_grid, parse_input, parse_str, Fields,
+    ParseError, ParseErrorKind, Separator, Values,
+};
+
+type FileLines = Lines<FileReader>;
+type ChunkReader = Take<BufReader<File>>;
+
+/// Opens a file for reading. Compressed files are decompressed on the fly,
+/// see `FileReader`.
+pub fn reader_from_file(filename: &str) -> Result<FileReader, Error> {
+    FileReader::open(filename)
+}
+
+/// Opens a file for reading its raw bytes, which can be split into chunks.
+fn seekable_reader_from_file(filename: &str) -> Result<BufReader<File>, Error> {
+    let mut reader = BufReader::new(File::open(filename)?);
+    if let Some(compression) = Compression::detect(reader.fill_buf()?) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "a {} compressed file cannot be split into chunks",
+                compression
+            ),
+        ));
+    }
+    Ok(reader)
+}
+
+pub fn lines_from_file(filename: &str) -> Result<FileLines, Error> {
+    Ok(reader_from_file(filename)?.lines())
+}
+
+/// A range of bytes of a file that holds whole lines: it starts at the
+/// beginning of a line and ends after a line break or at the end of the file.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Chunk {
+    pub start: u64,
+    pub end: u64,
+}
+
+/// Splits the file into at most `count` chunks of roughly the same size.
+/// The chunks are in file order and cover the whole file. Compressed files
+/// cannot be split.
+pub fn chunks_of_file(filename: &str, count: usize) -> Result<Vec<Chunk>, Error> {
+    let mut reader = seekable_reader_from_file(filename)?;
+    let size = reader.get_ref().metadata()?.len();
+    let count = count.max(1) as u128;
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut line = Vec::new();
+    for i in 1..=count {
+        let approximate_end = (u128::from(size) * i / count) as u64;
+        if approximate_end <= start {
+            continue;
+        }
+        // The chunk ends at the first line start at or after its approximate
+        // end, so the byte before that is where the search begins.
+        reader.seek(SeekFrom::Start(approximate_end - 1))?;
+        line.clear();
+        let end = approximate_end - 1 + reader.read_until(b'\n', &mut line)? as u64;
+        chunks.push(Chunk { start, end });
+        start = end;
+    }
+    Ok(chunks)
+}
+
+/// Returns a reader for a chunk of the file only.
+pub fn reader_of_chunk(filename: &str, chunk: Chunk) -> Result<ChunkReader, Error> {
+    let mut reader = seekable_reader_from_file(filename)?;
+    reader.seek(SeekFrom::Start(chunk.start))?;
+    Ok(reader.take(chunk.end - chunk.start))
+}
+
+/// Returns the lines of a chunk of the file, as `lines_from_file` would
+/// return them.
+pub fn lines_of_chunk(filename: &str, chunk: Chunk) -> Result<Lines<ChunkReader>, Error> {
+    Ok(reader_of_chunk(filename, chunk)?.lines())
+}
+
+/// Reads one integer per line. Blank lines are ignored.
+pub fn ints_from_file(filename: &str) -> Result<Vec<i64>, ParseError<ParseIntError>> {
+    parse_file(filename, Separator::Newline)
+}
+
+/// Parses one integer per line of a borrowed input, for example the
+/// contents of a `MappedFile`. Blank lines are ignored.
+pub fn ints_from_str(text: &str, file: &str) -> Result<Vec<i64>, ParseError<ParseIntError>> {
+    Tokens::new(text, Separator::Newline)
+        .map(|token| token.parse(file))
+        .collect()
+}
+
+#[cfg(test)]
+mod io_tests {
+    mod lines_from_file_test {
+        use super::super::lines_from_file;
+
+        #[test]
+        fn it_works_with_str() {
+            let lines: Vec<_> = lines_from_file("./data/test/io/lines_from_file/three_lines.txt")
+                .unwrap()
+                .collect();
+            assert_eq!(3, lines.len());
+        }
+
+        #[test]
+        fn it_works_with_string() {
+            let path = String::from("./data/test/io/lines_from_file/three_lines.txt");
+            let lines: Vec<_> = lines_from_file(&path).unwrap().collect();
+            assert_eq!(3, lines.len());
+        }
+    }
+
+    mod chunks_of_file_test {
+        use super::super::{chunks_of_file, lines_from_file, lines_of_chunk};
+
+        #[test]
+        fn it_splits_the_file_into_whole_lines() {
+            let path = "./data/input/1.txt";
+            let lines: Vec<String> = lines_from_file(path).unwrap().map(Result::unwrap).collect();
+            for count in &[1, 2, 3, 7, 64, 1000] {
+                let chunks = chunks_of_file(path, *count).unwrap();
+                assert!(chunks.len() <= *count);
+                assert_eq!(chunks[0].start, 0);
+                for pair in chunks.windows(2) {
+                    assert_eq!(pair[0].end, pair[1].start);
+                }
+
+                let mut chunked_lines = Vec::new();
+                for chunk in chunks {
+                    for line in lines_of_chunk(path, chunk).unwrap() {
+                        chunked_lines.push(line.unwrap());
+                    }
+                }
+                assert_eq!(chunked_lines, lines);
+            }
+        }
+
+        #[test]
+        fn it_returns_no_empty_chunks() {
+            let path = "./data/test/io/lines_from_file/three_lines.txt";
+            let chunks = chunks_of_file(path, 10).unwrap();
+            assert!(chunks.iter().all(|chunk| chunk.start < chunk.end));
+            let lines: usize = chunks
+                .into_iter()
+                .map(|chunk| lines_of_chunk(path, chunk).unwrap().count())
+                .sum();
+            assert_eq!(lines, 3);
+        }
+    }
+
+    mod ints_from_str_test {
+        use super::super::{ints_from_file, ints_from_str, MappedFile};
+
+        #[test]
+        fn it_parses_borrowed_input() {
+            let path = "./data/input/1.txt";
+            let file = MappedFile::open(path).unwrap();
+            let ints = ints_from_str(file.as_str().unwrap(), path).unwrap();
+            assert_eq!(ints, ints_from_file(path).unwrap());
+        }
+
+        #[test]
+        fn it_reports_the_position() {
+            let err = ints_from_str("1\n\n x", "<text>").unwrap_err();
+            assert_eq!((err.line, err.column), (3, 2));
+        }
+    }
+
+    mod ints_from_file_test {
+        use super::super::super::riddle::RiddleError;
+        use super::super::{ints_from_file, ParseErrorKind};
+
+        #[test]
+        fn it_works_in_normal_cases() {
+            let ints = ints_from_file("./data/test/io/ints_from_file/ints.txt").unwrap();
+
+            assert_eq!(ints, vec![1, -1, 100, 9999]);
+        }
+
+        #[test]
+        fn it_behaves_as_expected_when_it_cannot_parse() -> Result<(), String> {
+            let path = "./data/test/io/ints_from_file/no_ints.txt";
+            match ints_from_file(path) {
+                Err(err) => match err.kind {
+                    ParseErrorKind::Value { .. } => {
+                        assert_eq!((err.file.as_str(), err.line), (path, 3));
+                        assert_eq!(err.line_text, "foo");
+                        Ok(())
+                    }
+                    _ => Err(String::from("expected convert error")),
+                },
+                Ok(_) => Err(String::from("expected error")),
+            }
+        }
+
+        #[test]
+        fn it_converts_into_riddle_errors() {
+            fn sum(path: &str) -> Result<i64, RiddleError> {
+                Ok(ints_from_file(path)?.iter().sum())
+            }
+
+            assert_eq!(
+                sum("./data/test/io/ints_from_file/ints.txt").unwrap(),
+                10099
+            );
+            match sum("./data/test/io/ints_from_file/no_ints.txt") {
+                Err(RiddleError::Generic(message)) => assert!(message.contains("no_ints.txt:3:1")),
+                other => panic!("expected a generic error, got {:?}", other),
+            }
+            match sum("./does/not/exist") {
+                Err(RiddleError::IO(err)) => {
+                    assert!(err.to_string().starts_with("./does/not/exist: "))
+                }
+                other => panic!("expected an IO error, got {:?}", other),
+            }
+        }
+    }
+}
@@ -0,0 +1,640 @@
+//! Parsing of puzzle input into typed values and character grids.
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead};
+use std::marker::PhantomData;
+use std::str::FromStr;
+
+use super::super::geometry::Grid;
+use super::Input;
+
+/// How the values of an input are separated.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Separator {
+    /// One value per line.
+    Newline,
+    /// Values separated by commas, on one or more lines. A comma at the end
+    /// of a line is allowed. Commas inside parentheses do not separate
+    /// values, so `(1,2)` stays one value.
+    Comma,
+    /// Values separated by any amount of whitespace.
+    Whitespace,
+    /// Groups of lines separated by blank lines. Each value is a group with
+    /// its lines joined by `'\n'`.
+    BlankLine,
+}
+
+impl Separator {
+    /// Splits a single line into its values. Yields each value trimmed of
+    /// whitespace, together with its byte offset in the line. Blank lines
+    /// hold no values.
+    pub fn split(self, line: &str) -> Fields<'_> {
+        let position = if line.trim().is_empty() {
+            None
+        } else {
+            Some(0)
+        };
+        Fields {
+            line,
+            position,
+            separator: self,
+        }
+    }
+}
+
+/// The values of a line, see `Separator::split`.
+pub struct Fields<'a> {
+    line: &'a str,
+    position: Option<usize>,
+    separator: Separator,
+}
+
+impl<'a> Iterator for Fields<'a> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<(usize, &'a str)> {
+        let start = self.position?;
+        let rest = &self.line[start..];
+        let (raw_start, raw_end, next) = match self.separator {
+            Separator::Comma => {
+                let mut depth = 0;
+                let mut end = None;
+                for (i, c) in rest.char_indices() {
+                    match c {
+                        '(' => depth += 1,
+                        ')' => depth -= 1,
+                        ',' if depth == 0 => {
+                            end = Some(i);
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                match end {
+                    Some(end) if rest[end + 1..].trim().is_empty() => (0, end, None),
+                    Some(end) => (0, end, Some(start + end + 1)),
+                    None => (0, rest.len(), None),
+                }
+            }
+            Separator::Whitespace => {
+                let value_start = match rest.find(|c: char| !c.is_whitespace()) {
+                    Some(i) => i,
+                    None => {
+                        self.position = None;
+                        return None;
+                    }
+                };
+                let value_end = rest[value_start..]
+                    .find(char::is_whitespace)
+                    .map_or(rest.len(), |i| value_start + i);
+                (value_start, value_end, Some(start + value_end))
+            }
+            Separator::Newline | Separator::BlankLine => (0, rest.len(), None),
+        };
+        self.position = next;
+        let raw = &rest[raw_start..raw_end];
+        let offset = start + raw_start + (raw.len() - raw.trim_start().len());
+        Some((offset, raw.trim()))
+    }
+}
+
+/// An error while parsing an input, with the position of the value that
+/// could not be parsed. Lines and columns count from 1.
+#[derive(Debug)]
+pub struct ParseError<E> {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    /// The raw text of the failing line, or of the failing group of lines
+    /// for `Separator::BlankLine`. Empty if the input could not be read.
+    pub line_text: String,
+    pub kind: ParseErrorKind<E>,
+}
+
+#[derive(Debug)]
+pub enum ParseErrorKind<E> {
+    /// The input could not be read.
+    IO(io::Error),
+    /// The value could not be parsed.
+    Value { text: String, error: E },
+    /// A row of a grid is not as wide as the first one.
+    RaggedRow { width: usize, expected: usize },
+}
+
+impl<E> ParseError<E> {
+    fn io(file: &str, line: usize, err: io::Error) -> ParseError<E> {
+        ParseError {
+            file: file.to_string(),
+            line,
+            column: 0,
+            line_text: String::new(),
+            kind: ParseErrorKind::IO(err),
+        }
+    }
+}
+
+impl<E: fmt::Debug> fmt::Display for ParseError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ParseErrorKind::IO(err) => write!(f, "{}: {}", self.file, err),
+            ParseErrorKind::Value { text, error } => {
+                write!(
+                    f,
+                    "{}:{}:{}: cannot parse '{}'",
+                    self.file, self.line, self.column, text
+                )?;
+                if self.line_text != *text {
+                    write!(f, " in '{}'", self.line_text)?;
+                }
+                write!(f, ": {:?}", error)
+            }
+            ParseErrorKind::RaggedRow { width, expected } => write!(
+                f,
+                "{}:{}: the row '{}' is {} wide, expected {}",
+                self.file, self.line, self.line_text, width, expected
+            ),
+        }
+    }
+}
+
+impl<E: Error + 'static> Error for ParseError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            ParseErrorKind::IO(err) => Some(err),
+            ParseErrorKind::Value { error, .. } => Some(error),
+            ParseErrorKind::RaggedRow { .. } => None,
+        }
+    }
+}
+
+/// Reads values of type `T`, separated by a `Separator`, from a reader.
+/// Values that cannot be parsed are reported without ending the iteration,
+/// so callers can skip them. An error while reading ends it.
+pub struct Values<R, T> {
+    reader: R,
+    file: String,
+    separator: Separator,
+    line: String,
+    line_number: usize,
+    position: Option<usize>,
+    done: bool,
+    value: PhantomData<fn() -> T>,
+}
+
+impl<R: BufRead, T: FromStr> Values<R, T> {
+    /// `file` names the input in errors.
+    pub fn new(reader: R, file: &str, separator: Separator) -> Values<R, T> {
+        Values {
+            reader,
+            file: file.to_string(),
+            separator,
+            line: String::new(),
+            line_number: 0,
+            position: None,
+            done: false,
+            value: PhantomData,
+        }
+    }
+
+    /// The number of lines read so far.
+    pub fn lines_read(&self) -> usize {
+        self.line_number
+    }
+
+    /// Reads the next line that is not blank and parses the values on that
+    /// line only, handing each to `visit` as soon as it is parsed. Returns
+    /// `false` once the input is exhausted.
+    pub fn read_line<F: FnMut(T)>(&mut self, mut visit: F) -> Result<bool, ParseError<T::Err>> {
+        loop {
+            if !self.read_raw_line()? {
+                return Ok(false);
+            }
+            if !self.line.trim().is_empty() {
+                break;
+            }
+        }
+        self.position = None;
+        for (offset, text) in self.separator.split(&self.line) {
+            visit(self.parse(offset, text)?);
+        }
+        Ok(true)
+    }
+
+    /// Reads the next line into `self.line`, without its line break.
+    fn read_raw_line(&mut self) -> Result<bool, ParseError<T::Err>> {
+        self.line.clear();
+        match self.reader.read_line(&mut self.line) {
+            Ok(0) => Ok(false),
+            Ok(_) => {
+                self.line_number += 1;
+                if self.line.ends_with('\n') {
+                    self.line.pop();
+                    if self.line.ends_with('\r') {
+                        self.line.pop();
+                    }
+                }
+                Ok(true)
+            }
+            Err(err) => {
+                self.done = true;
+                Err(ParseError::io(&self.file, self.line_number + 1, err))
+            }
+        }
+    }
+
+    fn parse(&self, offset: usize, text: &str) -> Result<T, ParseError<T::Err>> {
+        text.parse().map_err(|error| ParseError {
+            file: self.file.clone(),
+            line: self.line_number,
+            column: self.line[..offset].chars().count() + 1,
+            line_text: self.line.clone(),
+            kind: ParseErrorKind::Value {
+                text: text.to_string(),
+                error,
+            },
+        })
+    }
+
+    fn next_group(&mut self) -> Option<Result<T, ParseError<T::Err>>> {
+        let mut group = String::new();
+        let mut first_line = 0;
+        loop {
+            match self.read_raw_line() {
+                Ok(true) if self.line.trim().is_empty() => {
+                    if !group.is_empty() {
+                        break;
+                    }
+                }
+                Ok(true) => {
+                    if group.is_empty() {
+                        first_line = self.line_number;
+                    } else {
+                        group.push('\n');
+                    }
+                    group.push_str(&self.line);
+                }
+                Ok(false) => {
+                    self.done = true;
+                    if group.is_empty() {
+                        return None;
+                    }
+                    break;
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        match group.parse() {
+            Ok(value) => Some(Ok(value)),
+            Err(error) => Some(Err(ParseError {
+                file: self.file.clone(),
+                line: first_line,
+                column: 1,
+                line_text: group.clone(),
+                kind: ParseErrorKind::Value { text: group, error },
+            })),
+        }
+    }
+}
+
+impl<R: BufRead, T: FromStr> Iterator for Values<R, T> {
+    type Item = Result<T, ParseError<T::Err>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if self.separator == Separator::BlankLine {
+            return self.next_group();
+        }
+        loop {
+            let mut fields = Fields {
+                line: &self.line,
+                position: self.position,
+                separator: self.separator,
+            };
+            if let Some((offset, text)) = fields.next() {
+                self.position = fields.position;
+                return Some(self.parse(offset, text));
+            }
+            match self.read_raw_line() {
+                Ok(true) => self.position = self.separator.split(&self.line).position,
+                Ok(false) => {
+                    self.done = true;
+                    return None;
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+/// Parses all values of a file.
+pub fn parse_file<T: FromStr>(
+    filename: &str,
+    separator: Separator,
+) -> Result<Vec<T>, ParseError<T::Err>> {
+    parse_input(&Input::path(filename), separator)
+}
+
+/// Parses all values of an input.
+pub fn parse_input<T: FromStr>(
+    input: &Input,
+    separator: Separator,
+) -> Result<Vec<T>, ParseError<T::Err>> {
+    let reader = input
+        .open()
+        .map_err(|err| ParseError::io(input.name(), 0, err))?;
+    Values::new(reader, input.name(), separator).collect()
+}
+
+/// Parses all values of a string, for example of an input given inline.
+pub fn parse_str<T: FromStr>(
+    input: &str,
+    separator: Separator,
+) -> Result<Vec<T>, ParseError<T::Err>> {
+    Values::new(input.as_bytes(), "<input>", separator).collect()
+}
+
+/// Reads a grid with one row per line and one cell per character. Lines may
+/// end in `\r\n`. All rows must be as wide as the first one. Blank lines,
+/// including those of only whitespace, are ignored at the end and are rows
+/// that are too short between rows.
+pub fn parse_grid<R, T>(reader: R, file: &str) -> Result<Grid<T>, ParseError<T::Error>>
+where
+    R: BufRead,
+    T: TryFrom<char>,
+{
+    let mut rows: Vec<Vec<T>> = Vec::new();
+    let mut blank_line = None;
+    for (i, line) in reader.lines().enumerate() {
+        let line_number = i + 1;
+        let mut line = line.map_err(|err| ParseError::io(file, line_number, err))?;
+        if line.ends_with('\r') {
+            line.pop();
+        }
+        if line.trim().is_empty() {
+            blank_line.get_or_insert(line_number);
+            continue;
+        }
+
+        let width = line.chars().count();
+        let expected = rows.first().map_or(width, Vec::len);
+        let ragged = match blank_line {
+            Some(blank_line) => Some((blank_line, String::new(), 0)),
+            None if width != expected => Some((line_number, line.clone(), width)),
+            None => None,
+        };
+        if let Some((line, line_text, width)) = ragged {
+            return Err(ParseError {
+                file: file.to_string(),
+                line,
+                column: width.min(expected) + 1,
+                line_text,
+                kind: ParseErrorKind::RaggedRow { width, expected },
+            });
+        }
+
+        let row: Result<Vec<T>, _> = line
+            .chars()
+            .enumerate()
+            .map(|(j, c)| {
+                T::try_from(c).map_err(|error| ParseError {
+                    file: file.to_string(),
+                    line: line_number,
+                    column: j + 1,
+                    line_text: line.clone(),
+                    kind: ParseErrorKind::Value {
+                        text: c.to_string(),
+                        error,
+                    },
+                })
+            })
+            .collect();
+        rows.push(row?);
+    }
+    Ok(Grid::from_rows(rows).expect("all rows have the same width"))
+}
+
+/// Reads a grid from a file, see `parse_grid`.
+pub fn grid_from_file<T: TryFrom<char>>(filename: &str) -> Result<Grid<T>, ParseError<T::Error>> {
+    grid_from_input(&Input::path(filename))
+}
+
+/// Reads a grid from an input, see `parse_grid`.
+pub fn grid_from_input<T: TryFrom<char>>(input: &Input) -> Result<Grid<T>, ParseError<T::Error>> {
+    let reader = input
+        .open()
+        .map_err(|err| ParseError::io(input.name(), 0, err))?;
+    parse_grid(reader, input.name())
+}
+
+#[cfg(test)]
+mod parse_tests {
+    mod values_tests {
+        use super::super::{parse_file, parse_str, ParseError, ParseErrorKind, Separator};
+        use std::error::Error;
+        use std::fmt;
+        use std::num::ParseIntError;
+
+        fn position<E: fmt::Debug>(
+            result: Result<Vec<i64>, ParseError<E>>,
+        ) -> (usize, usize, String) {
+            match result {
+                Err(ParseError {
+                    line,
+                    column,
+                    kind: ParseErrorKind::Value { text, .. },
+                    ..
+                }) => (line, column, text),
+                Err(err) => panic!("expected a value error, got {:?}", err.kind),
+                Ok(values) => panic!("expected an error, got {:?}", values),
+            }
+        }
+
+        #[test]
+        fn it_splits_at_newlines() {
+            let values: Vec<i64> = parse_str("1\n -2 \r\n\n3", Separator::Newline).unwrap();
+            assert_eq!(values, vec![1, -2, 3]);
+        }
+
+        #[test]
+        fn it_splits_at_commas() {
+            let values: Vec<i64> = parse_str("1,2, 3\n4,5\n", Separator::Comma).unwrap();
+            assert_eq!(values, vec![1, 2, 3, 4, 5]);
+        }
+
+        #[test]
+        fn it_keeps_commas_inside_parentheses() {
+            let values: Vec<String> = parse_str("a,(1,2),b", Separator::Comma).unwrap();
+            assert_eq!(values, vec!["a", "(1,2)", "b"]);
+        }
+
+        #[test]
+        fn it_splits_at_whitespace() {
+            let values: Vec<i64> = parse_str(" 1\t2  3\n\n 4 ", Separator::Whitespace).unwrap();
+            assert_eq!(values, vec![1, 2, 3, 4]);
+        }
+
+        #[test]
+        fn it_splits_at_blank_lines() {
+            let values: Vec<String> = parse_str("\na\nb\n\n\nc\n", Separator::BlankLine).unwrap();
+            assert_eq!(values, vec!["a\nb", "c"]);
+        }
+
+        #[test]
+        fn it_reports_where_a_value_is_invalid() {
+            let result = parse_str::<i64>("1,2\n3, x4,5", Separator::Comma);
+            assert_eq!(position(result), (2, 4, "x4".to_string()));
+
+            let result = parse_str::<i64>("1 2\n\n3  x4", Separator::Whitespace);
+            assert_eq!(position(result), (3, 4, "x4".to_string()));
+
+            let result = parse_str::<i64>("1\n\n2\nx\n", Separator::BlankLine);
+            assert_eq!(position(result), (3, 1, "2\nx".to_string()));
+        }
+
+        #[test]
+        fn it_keeps_the_failing_line() {
+            let err = parse_str::<i64>("1,2\n3, x4,5", Separator::Comma).unwrap_err();
+            assert_eq!(err.line_text, "3, x4,5");
+            assert!(err
+                .to_string()
+                .starts_with("<input>:2:4: cannot parse 'x4' in '3, x4,5': ParseIntError"));
+            assert!(err.source().is_some());
+        }
+
+        #[test]
+        fn it_reports_empty_values() {
+            let result = parse_str::<i64>("1,,2", Separator::Comma);
+            assert_eq!(position(result), (1, 3, String::new()));
+        }
+
+        #[test]
+        fn it_names_the_file() {
+            let path = "./data/test/io/ints_from_file/no_ints.txt";
+            let err = parse_file::<i64>(path, Separator::Newline).unwrap_err();
+            assert_eq!(err.file, path);
+            assert_eq!(err.line, 3);
+            assert!(err
+                .to_string()
+                .starts_with(&format!("{}:3:1: cannot parse 'foo'", path)));
+        }
+
+        #[test]
+        fn it_reports_missing_files() {
+            match parse_file::<i64>("./does/not/exist", Separator::Newline) {
+                Err(ParseError {
+                    kind: ParseErrorKind::IO(_),
+                    ..
+                }) => {}
+                other => panic!("expected an IO error, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn it_keeps_going_after_invalid_values() {
+            let values: Vec<Result<i64, ParseError<ParseIntError>>> =
+                super::super::Values::new("1\nx\n3".as_bytes(), "<input>", Separator::Newline)
+                    .collect();
+            assert_eq!(values.len(), 3);
+            assert!(values[1].is_err());
+            assert_eq!(*values[2].as_ref().unwrap(), 3);
+        }
+    }
+
+    mod grid_tests {
+        use super::super::super::super::geometry::{Grid, Point};
+        use super::super::{parse_grid, ParseError, ParseErrorKind};
+        use std::convert::TryFrom;
+        use std::fs::File;
+        use std::io::BufReader;
+
+        #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+        enum Tile {
+            Open,
+            Wall,
+        }
+
+        impl TryFrom<char> for Tile {
+            type Error = char;
+
+            fn try_from(c: char) -> Result<Tile, char> {
+                match c {
+                    '.' => Ok(Tile::Open),
+                    '#' => Ok(Tile::Wall),
+                    c => Err(c),
+                }
+            }
+        }
+
+        #[test]
+        fn it_reads_characters() {
+            let grid: Grid<char> = parse_grid("ab\ncd\n\n".as_bytes(), "<input>").unwrap();
+            assert_eq!((grid.width(), grid.height()), (2, 2));
+            assert_eq!(grid[Point::new(1, 0)], 'b');
+            assert_eq!(grid[Point::new(0, 1)], 'c');
+        }
+
+        #[test]
+        fn it_converts_cells() {
+            let grid: Grid<Tile> = parse_grid(".#\n#.".as_bytes(), "<input>").unwrap();
+            assert_eq!(grid[Point::new(1, 0)], Tile::Wall);
+            assert_eq!(grid[Point::new(1, 1)], Tile::Open);
+        }
+
+        #[test]
+        fn it_reports_invalid_cells() {
+            match parse_grid::<_, Tile>(".#\n#x".as_bytes(), "<input>") {
+                Err(ParseError {
+                    line: 2,
+                    column: 2,
+                    kind: ParseErrorKind::Value { error: 'x', .. },
+                    ..
+                }) => {}
+                other => panic!("expected an invalid cell, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn it_reports_ragged_rows() {
+            match parse_grid::<_, char>("abc\nabc\nab\n".as_bytes(), "<input>") {
+                Err(ParseError {
+                    line: 3,
+                    column: 3,
+                    kind:
+                        ParseErrorKind::RaggedRow {
+                            width: 2,
+                            expected: 3,
+                        },
+                    ..
+                }) => {}
+                other => panic!("expected a ragged row, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn it_reads_crlf_grids() {
+            let path = "./data/test/io/grid_crlf.txt";
+            let grid: Grid<Tile> =
+                parse_grid(File::open(path).map(BufReader::new).unwrap(), path).unwrap();
+            assert_eq!((grid.width(), grid.height()), (3, 2));
+            assert_eq!(grid[Point::new(2, 0)], Tile::Open);
+            assert_eq!(grid[Point::new(2, 1)], Tile::Wall);
+
+            let grid: Grid<char> = parse_grid("ab\ncd\n\r\n  \n\r".as_bytes(), "<input>").unwrap();
+            assert_eq!((grid.width(), grid.height()), (2, 2));
+        }
+
+        #[test]
+        fn it_reports_blank_lines_between_rows() {
+            let err = parse_grid::<_, char>("ab\n\nab\n".as_bytes(), "<input>").unwrap_err();
+            assert_eq!((err.line, err.column), (2, 1));
+            assert_eq!(
+                err.to_string(),
+                "<input>:2: the row '' is 0 wide, expected 2"
+            );
+        }
+    }
+}
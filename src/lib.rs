@@ -1,2 +1,8 @@
+pub mod bigint;
+pub mod cli;
+pub mod geometry;
 pub mod io;
+pub mod json;
 pub mod riddle;
+#[cfg(test)]
+mod testing;
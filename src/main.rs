@@ -1,31 +1,17 @@
 use std::env;
-use std::io::{Error, ErrorKind};
+use std::io;
+use std::process;
 use std::vec::Vec;
 
-use aoc19::riddle::riddlecontainer::RiddleContainer;
+use aoc19::cli;
 
-fn main() -> Result<(), std::io::Error> {
-    let riddles = RiddleContainer::new();
-
-    let args = {
-        let args: Vec<String> = env::args().collect();
-        if args.len() <= 1 {
-            return Err(Error::new(
-                ErrorKind::Other,
-                "expected at least one argument",
-            ));
-        }
-        args
-    };
-
-    let riddle = args[1].to_string();
-    let remaining_args = if args.len() == 1 {
-        Vec::<String>::new()
-    } else {
-        args[1..].to_vec()
-    };
-
-    let result = riddles.solve_riddle(riddle, &remaining_args)?;
-    println!("result: {:?}", result);
-    Ok(())
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let code = cli::main(
+        &args,
+        &mut io::BufReader::new(io::stdin()),
+        &mut io::stdout(),
+        &mut io::stderr(),
+    );
+    process::exit(code);
 }
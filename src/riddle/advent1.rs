@@ -1,79 +1,987 @@
-use super::super::io::ints_from_file;
-use super::{Riddle, RiddleError, Solution};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::hash::{BuildHasherDefault, Hasher};
+use std::io::BufRead;
+use std::num::ParseIntError;
+use std::str;
+use std::thread;
+
+use super::super::io::{
+    chunks_of_file, compression_of_file, reader_of_chunk, Chunk, Input, ParseError, ParseErrorKind,
+    Separator, Values,
+};
+use super::super::json::Json;
+use super::args::{ArgKind, ArgSchema, ArgSpec};
+use super::{Notes, Riddle, RiddleError, SharedInputs, Solution};
+
+riddles! {
+    Advent1Riddle1 {
+        day: 1,
+        part: 1,
+        name: "The Tyranny of the Rocket Equation",
+        input: "1.txt",
+        args: FUEL_ARGS,
+    },
+    Advent1Riddle2 {
+        day: 1,
+        part: 2,
+        name: "The Tyranny of the Rocket Equation",
+        input: "1.txt",
+        args: FUEL_ARGS,
+    },
+}
 
 pub struct Advent1Riddle1 {
-    input_file: String,
+    input: Input,
 }
 
 impl Advent1Riddle1 {
-    pub fn new(input_file: &str) -> Advent1Riddle1 {
-        let input_file = input_file.to_string();
-        Advent1Riddle1 { input_file }
+    pub fn new<I: Into<Input>>(input: I) -> Advent1Riddle1 {
+        let input = input.into();
+        Advent1Riddle1 { input }
+    }
+
+    /// A riddle that streams its input, so it shares no parsed input.
+    pub fn build(input: Input, _: &mut SharedInputs) -> Advent1Riddle1 {
+        Advent1Riddle1::new(input)
     }
 }
 
 impl Riddle for Advent1Riddle1 {
-    fn solve(&self, _: &[String]) -> Result<Solution, RiddleError> {
-        let ints = ints_from_file(&self.input_file).unwrap();
-
-        let result = ints.iter().map(|&x| calculate_fuel(x)).sum();
-        Ok(Solution::Number(result))
+    fn solve(&self, args: &[String]) -> Result<Solution, RiddleError> {
+        let options = FuelOptions::from_args(args)?;
+        let model = options.model;
+        solve_fuel(&self.input, || move |mass| model.fuel(mass), &options)
     }
 }
 
 pub struct Advent1Riddle2 {
-    input_file: String,
+    input: Input,
 }
 
 impl Advent1Riddle2 {
-    pub fn new(input_file: &str) -> Advent1Riddle2 {
-        let input_file = input_file.to_string();
-        Advent1Riddle2 { input_file }
+    pub fn new<I: Into<Input>>(input: I) -> Advent1Riddle2 {
+        let input = input.into();
+        Advent1Riddle2 { input }
+    }
+
+    /// A riddle that streams its input, so it shares no parsed input.
+    pub fn build(input: Input, _: &mut SharedInputs) -> Advent1Riddle2 {
+        Advent1Riddle2::new(input)
     }
 }
 
 impl Riddle for Advent1Riddle2 {
-    fn solve(&self, _: &[String]) -> Result<Solution, RiddleError> {
-        let ints = ints_from_file(&self.input_file).unwrap();
+    fn solve(&self, args: &[String]) -> Result<Solution, RiddleError> {
+        let options = FuelOptions::from_args(args)?;
+        let model = options.model;
+        if options.memo {
+            let make_fuel = || {
+                let mut memo = FuelMemo::new(model);
+                move |mass| memo.fuel_recursive(mass)
+            };
+            solve_fuel(&self.input, make_fuel, &options)
+        } else {
+            let make_fuel = || move |mass| model.fuel_recursive(mass);
+            solve_fuel(&self.input, make_fuel, &options)
+        }
+    }
+}
+
+/// Sums up the fuel of the modules as the answer, with the report if the
+/// options ask for one and a warning about the lines skipped in lenient
+/// mode. The report needs the modules in order, so it is built in the same
+/// pass as the sum, which then never runs in parallel.
+fn solve_fuel<M, F>(
+    input: &Input,
+    make_fuel: M,
+    options: &FuelOptions,
+) -> Result<Solution, RiddleError>
+where
+    M: Fn() -> F + Sync,
+    F: FnMut(i64) -> Result<i64, FuelError>,
+{
+    let mut notes = Notes::default();
+    let sum = match options.report {
+        Some(format) => {
+            let (report, sum) = report_and_sum(input, make_fuel(), options)?;
+            notes.report = Some(report.render(format).trim_end().to_string());
+            sum
+        }
+        None => sum_modules(input, make_fuel, options)?,
+    };
+    if sum.skipped > 0 {
+        notes.warnings.push(format!(
+            "skipped {} invalid line(s) in {}",
+            sum.skipped,
+            input.name()
+        ));
+    }
+    Ok(Solution::Number(sum.total).with_notes(notes))
+}
+
+/// The options both riddles of day 1 accept. With `lenient=true`, lines that
+/// do not hold a valid mass are skipped instead of failing the riddle. With
+/// `report=table|csv|json`, the solution notes a per-module breakdown.
+/// `divisor`, `subtrahend`, `rounding` and `floor-at-zero` configure the
+/// `FuelModel`. With `threads=N`, the input is split into N chunks that are
+/// summed up in parallel. With `memo=true`, the second riddle shares a
+/// `FuelMemo` across the modules of a chunk, so each thread has its own.
+#[derive(Debug, PartialEq, Eq, Default)]
+pub struct FuelOptions {
+    lenient: bool,
+    report: Option<ReportFormat>,
+    model: FuelModel,
+    memo: bool,
+    threads: usize,
+}
+
+/// The options of both riddles of day 1, see `FuelOptions`.
+pub const FUEL_ARGS: ArgSchema = ArgSchema {
+    options: &[
+        ArgSpec {
+            name: "lenient",
+            kind: ArgKind::Bool,
+            help: "skips lines that do not hold a valid mass",
+        },
+        ArgSpec {
+            name: "report",
+            kind: ArgKind::Choice(&["table", "csv", "json"]),
+            help: "reports the fuel per module with the answer",
+        },
+        ArgSpec {
+            name: "divisor",
+            kind: ArgKind::PositiveInteger,
+            help: "divides the mass (default 3)",
+        },
+        ArgSpec {
+            name: "subtrahend",
+            kind: ArgKind::Integer,
+            help: "is subtracted from the quotient (default 2)",
+        },
+        ArgSpec {
+            name: "rounding",
+            kind: ArgKind::Choice(&["floor", "ceil", "nearest"]),
+            help: "rounds the quotient (default floor)",
+        },
+        ArgSpec {
+            name: "floor-at-zero",
+            kind: ArgKind::Bool,
+            help: "treats negative fuel as no fuel (default true)",
+        },
+        ArgSpec {
+            name: "memo",
+            kind: ArgKind::Bool,
+            help: "shares a fuel memo across the modules of each thread in part 2",
+        },
+        ArgSpec {
+            name: "threads",
+            kind: ArgKind::PositiveInteger,
+            help: "sums up the input in that many chunks in parallel",
+        },
+    ],
+};
+
+impl FuelOptions {
+    fn from_args(args: &[String]) -> Result<FuelOptions, RiddleError> {
+        let args = FUEL_ARGS.parse(args)?;
+        let defaults = FuelModel::default();
+        Ok(FuelOptions {
+            lenient: args.flag("lenient"),
+            report: args.value("report")?,
+            model: FuelModel {
+                divisor: args.value("divisor")?.unwrap_or(defaults.divisor),
+                subtrahend: args.value("subtrahend")?.unwrap_or(defaults.subtrahend),
+                rounding: args.value("rounding")?.unwrap_or(defaults.rounding),
+                floor_at_zero: args
+                    .value("floor-at-zero")?
+                    .unwrap_or(defaults.floor_at_zero),
+            },
+            memo: args.flag("memo"),
+            threads: args.value("threads")?.unwrap_or_default(),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct FuelSum {
+    pub total: i64,
+    /// The number of lines skipped in lenient mode.
+    pub skipped: usize,
+}
+
+/// Sums up the fuel for all modules in the input, in parallel if the options
+/// ask for more than one thread and the input is an uncompressed file.
+/// `make_fuel` is called once per thread.
+fn sum_modules<M, F>(
+    input: &Input,
+    make_fuel: M,
+    options: &FuelOptions,
+) -> Result<FuelSum, ModuleError>
+where
+    M: Fn() -> F + Sync,
+    F: FnMut(i64) -> Result<i64, FuelError>,
+{
+    match input.as_path() {
+        Some(filename) if options.threads > 1 && compression_of_file(filename)?.is_none() => {
+            sum_fuel_parallel(filename, make_fuel, options, options.threads)
+        }
+        _ => sum_fuel(input, make_fuel(), options),
+    }
+}
+
+/// Sums up the fuel for all modules in the input. The input is read line by
+/// line, so this needs constant memory however large a file is.
+fn sum_fuel<F>(input: &Input, mut fuel: F, options: &FuelOptions) -> Result<FuelSum, ModuleError>
+where
+    F: FnMut(i64) -> Result<i64, FuelError>,
+{
+    let mut total: i64 = 0;
+    let skipped = for_each_module(input, options, |_, mass| {
+        total = total
+            .checked_add(fuel(mass)?)
+            .ok_or(FuelError::Overflow(mass))?;
+        Ok(())
+    })?;
+    Ok(FuelSum { total, skipped })
+}
+
+/// Sums up the fuel for all modules in the file like `sum_fuel`, but splits
+/// the file into up to `threads` chunks that are summed up in parallel, each
+/// with its own fuel function from `make_fuel`. The result and the errors
+/// are the same as those of `sum_fuel`.
+fn sum_fuel_parallel<M, F>(
+    filename: &str,
+    make_fuel: M,
+    options: &FuelOptions,
+    threads: usize,
+) -> Result<FuelSum, ModuleError>
+where
+    M: Fn() -> F + Sync,
+    F: FnMut(i64) -> Result<i64, FuelError>,
+{
+    let chunks = chunks_of_file(filename, threads)?;
+    let make_fuel = &make_fuel;
+    let sums: Vec<ChunkSum> = thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .iter()
+            .map(|&chunk| scope.spawn(move || sum_chunk(filename, chunk, make_fuel(), options)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    });
+
+    let mut lines = 0;
+    let mut total: i128 = 0;
+    let mut skipped = 0;
+    for sum in sums {
+        if total + sum.min_prefix < i128::from(i64::MIN)
+            || total + sum.max_prefix > i128::from(i64::MAX)
+        {
+            // The running total leaves the range of i64 somewhere in this
+            // chunk. Which module is to blame, and whether it is skipped in
+            // lenient mode, depends on the order of the additions, so this is
+            // left to the sequential sum.
+            return sum_fuel(&Input::path(filename), make_fuel(), options);
+        }
+        if let Some(err) = sum.error {
+            return Err(err.after_lines(lines));
+        }
+        lines += sum.lines;
+        total += sum.total;
+        skipped += sum.skipped;
+    }
+    Ok(FuelSum {
+        total: total as i64,
+        skipped,
+    })
+}
+
+/// The fuel of the modules in a chunk of the input. The total is kept as an
+/// i128 together with the smallest and the largest running total, so that
+/// overflows can be detected once the totals of the chunks before are known.
+#[derive(Debug, Default)]
+struct ChunkSum {
+    lines: usize,
+    total: i128,
+    min_prefix: i128,
+    max_prefix: i128,
+    skipped: usize,
+    /// The first error in the chunk, with a line number relative to the
+    /// start of the chunk. The other fields only cover the lines before it.
+    error: Option<ModuleError>,
+}
+
+fn sum_chunk<F>(filename: &str, chunk: Chunk, mut fuel: F, options: &FuelOptions) -> ChunkSum
+where
+    F: FnMut(i64) -> Result<i64, FuelError>,
+{
+    let mut sum = ChunkSum::default();
+    let result = reader_of_chunk(filename, chunk)
+        .map_err(ModuleError::from)
+        .and_then(|reader| {
+            let masses = Values::new(reader, filename, Separator::Newline);
+            visit_modules(masses, options, |_, mass| {
+                sum.total += i128::from(fuel(mass)?);
+                sum.min_prefix = sum.min_prefix.min(sum.total);
+                sum.max_prefix = sum.max_prefix.max(sum.total);
+                Ok(())
+            })
+        });
+    match result {
+        Ok((lines, skipped)) => {
+            sum.lines = lines;
+            sum.skipped = skipped;
+        }
+        Err(err) => sum.error = Some(err),
+    }
+    sum
+}
 
-        let result = ints.iter().map(|&x| calculate_fuel_recursive(x)).sum();
-        Ok(Solution::Number(result))
+/// Calls `visit` with the line number and mass of every module in the input,
+/// one mass per line. Blank lines are ignored. Returns the number of lines
+/// skipped in lenient mode.
+fn for_each_module<F>(input: &Input, options: &FuelOptions, visit: F) -> Result<usize, ModuleError>
+where
+    F: FnMut(usize, i64) -> Result<(), FuelError>,
+{
+    let masses = Values::new(input.open()?, input.name(), Separator::Newline);
+    let (_, skipped) = visit_modules(masses, options, visit)?;
+    Ok(skipped)
+}
+
+/// Calls `visit` with the line number and mass of every module in `masses`.
+/// Returns the number of lines read and the number of lines skipped in
+/// lenient mode.
+fn visit_modules<R, F>(
+    mut masses: Values<R, i64>,
+    options: &FuelOptions,
+    mut visit: F,
+) -> Result<(usize, usize), ModuleError>
+where
+    R: BufRead,
+    F: FnMut(usize, i64) -> Result<(), FuelError>,
+{
+    let mut skipped = 0;
+    while let Some(mass) = masses.next() {
+        match visit_module(masses.lines_read(), mass, &mut visit) {
+            Ok(()) => {}
+            Err(ModuleError::IO(err)) => return Err(ModuleError::IO(err)),
+            Err(_) if options.lenient => skipped += 1,
+            Err(err) => return Err(err),
+        }
     }
+    Ok((masses.lines_read(), skipped))
 }
 
-pub fn calculate_fuel(mass: i64) -> i64 {
-    assert!(mass >= 0);
-    if mass <= 6 {
-        0
-    } else {
-        mass / 3 - 2
+fn visit_module<F>(
+    line_number: usize,
+    mass: Result<i64, ParseError<ParseIntError>>,
+    visit: &mut F,
+) -> Result<(), ModuleError>
+where
+    F: FnMut(usize, i64) -> Result<(), FuelError>,
+{
+    let mass = mass?;
+    visit(line_number, mass).map_err(|error| ModuleError::Fuel {
+        line: line_number,
+        mass,
+        error,
+    })
+}
+
+/// Builds the report of the modules in the input and sums up their fuel with
+/// `fuel` in the same pass. A module that fails either is skipped by both in
+/// lenient mode.
+fn report_and_sum<F>(
+    input: &Input,
+    mut fuel: F,
+    options: &FuelOptions,
+) -> Result<(FuelReport, FuelSum), ModuleError>
+where
+    F: FnMut(i64) -> Result<i64, FuelError>,
+{
+    let mut modules = Vec::new();
+    let mut running_total: i64 = 0;
+    let mut total: i64 = 0;
+    let skipped = for_each_module(input, options, |line, mass| {
+        let fuel_chain = options.model.fuel_chain(mass)?;
+        let module_total = fuel_chain
+            .iter()
+            .try_fold(0i64, |total, &fuel| total.checked_add(fuel))
+            .ok_or(FuelError::Overflow(mass))?;
+        let next_running_total = running_total
+            .checked_add(module_total)
+            .ok_or(FuelError::Overflow(mass))?;
+        let next_total = total
+            .checked_add(fuel(mass)?)
+            .ok_or(FuelError::Overflow(mass))?;
+        running_total = next_running_total;
+        total = next_total;
+        modules.push(ModuleFuel {
+            line,
+            mass,
+            fuel: fuel_chain.first().cloned().unwrap_or(0),
+            fuel_chain,
+            module_total,
+            running_total,
+        });
+        Ok(())
+    })?;
+    Ok((FuelReport { modules }, FuelSum { total, skipped }))
+}
+
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum ReportFormat {
+    Table,
+    Csv,
+    Json,
+}
+
+impl str::FromStr for ReportFormat {
+    type Err = RiddleError;
+
+    fn from_str(s: &str) -> Result<Self, RiddleError> {
+        match s {
+            "table" => Ok(ReportFormat::Table),
+            "csv" => Ok(ReportFormat::Csv),
+            "json" => Ok(ReportFormat::Json),
+            _ => Err(RiddleError::Generic(format!(
+                "unknown report format '{}'",
+                s
+            ))),
+        }
+    }
+}
+
+/// The fuel needed by a single module.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ModuleFuel {
+    pub line: usize,
+    pub mass: i64,
+    /// The fuel for the mass of the module itself.
+    pub fuel: i64,
+    /// The fuel for the module, then the fuel for that fuel and so on.
+    pub fuel_chain: Vec<i64>,
+    /// The sum of the fuel chain.
+    pub module_total: i64,
+    /// The sum of the module totals up to and including this module.
+    pub running_total: i64,
+}
+
+/// A per-module breakdown of the fuel requirements.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FuelReport {
+    pub modules: Vec<ModuleFuel>,
+}
+
+impl FuelReport {
+    pub fn from_file(filename: &str, options: &FuelOptions) -> Result<FuelReport, ModuleError> {
+        FuelReport::from_input(&Input::path(filename), options)
+    }
+
+    pub fn from_input(input: &Input, options: &FuelOptions) -> Result<FuelReport, ModuleError> {
+        let (report, _) = report_and_sum(input, |_| Ok(0), options)?;
+        Ok(report)
+    }
+
+    /// The fuel for the modules alone, the answer to the first riddle.
+    pub fn total_fuel(&self) -> i64 {
+        self.modules.iter().map(|m| m.fuel).sum()
+    }
+
+    /// The fuel including the fuel for the fuel, the answer to the second
+    /// riddle.
+    pub fn total_with_fuel_for_fuel(&self) -> i64 {
+        self.modules.last().map_or(0, |m| m.running_total)
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Table => self.to_table(),
+            ReportFormat::Csv => self.to_csv(),
+            ReportFormat::Json => self.to_json().to_string(),
+        }
+    }
+
+    pub fn to_table(&self) -> String {
+        let header = [
+            "line",
+            "mass",
+            "fuel",
+            "module total",
+            "running total",
+            "fuel chain",
+        ];
+        let rows: Vec<[String; 6]> = self
+            .modules
+            .iter()
+            .map(|m| {
+                [
+                    m.line.to_string(),
+                    m.mass.to_string(),
+                    m.fuel.to_string(),
+                    m.module_total.to_string(),
+                    m.running_total.to_string(),
+                    join(&m.fuel_chain, " + "),
+                ]
+            })
+            .collect();
+
+        let mut widths: Vec<usize> = header.iter().map(|h| h.len()).collect();
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row.iter()) {
+                *width = (*width).max(cell.len());
+            }
+        }
+
+        let mut table = String::new();
+        let mut push_row = |cells: &[&str]| {
+            let last = cells.len() - 1;
+            let line: Vec<String> = cells
+                .iter()
+                .enumerate()
+                .map(|(i, cell)| {
+                    if i == last {
+                        cell.to_string()
+                    } else {
+                        format!("{:>width$}", cell, width = widths[i])
+                    }
+                })
+                .collect();
+            table.push_str(line.join("  ").trim_end());
+            table.push('\n');
+        };
+        push_row(&header);
+        for row in &rows {
+            let cells: Vec<&str> = row.iter().map(|c| c.as_str()).collect();
+            push_row(&cells);
+        }
+        table.push_str(&format!(
+            "total fuel: {}, including fuel for fuel: {}",
+            self.total_fuel(),
+            self.total_with_fuel_for_fuel()
+        ));
+        table
+    }
+
+    /// One row per module. The fuel chain is joined with semicolons.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("line,mass,fuel,module_total,running_total,fuel_chain\n");
+        for m in &self.modules {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                m.line,
+                m.mass,
+                m.fuel,
+                m.module_total,
+                m.running_total,
+                join(&m.fuel_chain, ";")
+            ));
+        }
+        csv
+    }
+
+    pub fn to_json(&self) -> Json {
+        let modules = self
+            .modules
+            .iter()
+            .map(|m| {
+                Json::object(vec![
+                    ("line", Json::Int(m.line as i64)),
+                    ("mass", Json::Int(m.mass)),
+                    ("fuel", Json::Int(m.fuel)),
+                    (
+                        "fuel_chain",
+                        Json::Array(m.fuel_chain.iter().map(|&f| Json::Int(f)).collect()),
+                    ),
+                    ("module_total", Json::Int(m.module_total)),
+                    ("running_total", Json::Int(m.running_total)),
+                ])
+            })
+            .collect();
+        Json::object(vec![
+            ("modules", Json::Array(modules)),
+            ("total_fuel", Json::Int(self.total_fuel())),
+            (
+                "total_with_fuel_for_fuel",
+                Json::Int(self.total_with_fuel_for_fuel()),
+            ),
+        ])
+    }
+}
+
+fn join(values: &[i64], separator: &str) -> String {
+    let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+    values.join(separator)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FuelError {
+    NegativeMass(i64),
+    /// The fuel for the mass does not fit into an `i64`.
+    Overflow(i64),
+    /// Under the fuel model, the fuel for the fuel for the mass never stops
+    /// growing.
+    Diverges(i64),
+}
+
+impl fmt::Display for FuelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FuelError::NegativeMass(mass) => write!(f, "the mass {} is negative", mass),
+            FuelError::Overflow(mass) => {
+                write!(f, "the fuel for mass {} does not fit into an i64", mass)
+            }
+            FuelError::Diverges(mass) => {
+                write!(f, "the fuel for mass {} never stops growing", mass)
+            }
+        }
+    }
+}
+
+impl Error for FuelError {}
+
+/// An error for a single module of the input.
+#[derive(Debug)]
+pub enum ModuleError {
+    IO(std::io::Error),
+    /// The line does not hold a mass.
+    Parse {
+        line: usize,
+        text: String,
+    },
+    /// The fuel for the mass on the line cannot be calculated.
+    Fuel {
+        line: usize,
+        mass: i64,
+        error: FuelError,
+    },
+}
+
+impl ModuleError {
+    /// Moves the line of the error down by `lines`, for errors found in a
+    /// part of the input that starts after that many lines.
+    fn after_lines(self, lines: usize) -> Self {
+        match self {
+            ModuleError::IO(err) => ModuleError::IO(err),
+            ModuleError::Parse { line, text } => ModuleError::Parse {
+                line: line + lines,
+                text,
+            },
+            ModuleError::Fuel { line, mass, error } => ModuleError::Fuel {
+                line: line + lines,
+                mass,
+                error,
+            },
+        }
+    }
+}
+
+impl fmt::Display for ModuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ModuleError::IO(err) => write!(f, "could not read masses: {}", err),
+            ModuleError::Parse { line, text } => {
+                write!(f, "line {}: '{}' is not a mass", line, text)
+            }
+            ModuleError::Fuel { line, error, .. } => {
+                write!(f, "line {}: cannot calculate the fuel: {}", line, error)
+            }
+        }
+    }
+}
+
+impl Error for ModuleError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ModuleError::IO(err) => Some(err),
+            ModuleError::Parse { .. } => None,
+            ModuleError::Fuel { error, .. } => Some(error),
+        }
+    }
+}
+
+impl From<std::io::Error> for ModuleError {
+    fn from(err: std::io::Error) -> Self {
+        ModuleError::IO(err)
+    }
+}
+
+impl From<ParseError<ParseIntError>> for ModuleError {
+    fn from(err: ParseError<ParseIntError>) -> Self {
+        match err.kind {
+            ParseErrorKind::IO(err) => ModuleError::IO(err),
+            ParseErrorKind::Value { text, .. } => ModuleError::Parse {
+                line: err.line,
+                text,
+            },
+            ParseErrorKind::RaggedRow { .. } => unreachable!("masses are not grids"),
+        }
+    }
+}
+
+/// Lines that are not masses fail like the input of other riddles that
+/// cannot be parsed, fuel that cannot be calculated fails the riddle.
+impl From<ModuleError> for RiddleError {
+    fn from(err: ModuleError) -> Self {
+        match err {
+            ModuleError::IO(err) => RiddleError::IO(err),
+            err @ ModuleError::Parse { .. } => {
+                RiddleError::Parse(Box::new(RiddleError::Generic(err.to_string())))
+            }
+            err @ ModuleError::Fuel { .. } => RiddleError::Generic(err.to_string()),
+        }
+    }
+}
+
+/// How the division in the rocket equation is rounded.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum Rounding {
+    Floor,
+    Ceil,
+    /// Rounds to the nearest integer, halves are rounded up.
+    Nearest,
+}
+
+impl Rounding {
+    /// Divides a non-negative dividend by a positive divisor.
+    fn divide(self, dividend: i64, divisor: i64) -> i64 {
+        let quotient = dividend / divisor;
+        let remainder = dividend % divisor;
+        let round_up = match self {
+            Rounding::Floor => false,
+            Rounding::Ceil => remainder != 0,
+            Rounding::Nearest => remainder >= divisor - remainder,
+        };
+        if round_up {
+            quotient + 1
+        } else {
+            quotient
+        }
+    }
+}
+
+impl str::FromStr for Rounding {
+    type Err = RiddleError;
+
+    fn from_str(s: &str) -> Result<Self, RiddleError> {
+        match s {
+            "floor" => Ok(Rounding::Floor),
+            "ceil" => Ok(Rounding::Ceil),
+            "nearest" => Ok(Rounding::Nearest),
+            _ => Err(RiddleError::Generic(format!("unknown rounding '{}'", s))),
+        }
+    }
+}
+
+/// The rocket equation. The fuel for a mass is `mass / divisor - subtrahend`,
+/// with the division rounded as configured. With `floor_at_zero`, negative
+/// fuel is treated as no fuel at all.
+///
+/// The default model is the one of the puzzle: divide by three, round down
+/// and subtract two.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct FuelModel {
+    pub divisor: i64,
+    pub subtrahend: i64,
+    pub rounding: Rounding,
+    pub floor_at_zero: bool,
+}
+
+impl Default for FuelModel {
+    fn default() -> Self {
+        FuelModel {
+            divisor: 3,
+            subtrahend: 2,
+            rounding: Rounding::Floor,
+            floor_at_zero: true,
+        }
+    }
+}
+
+impl FuelModel {
+    pub fn fuel(&self, mass: i64) -> Result<i64, FuelError> {
+        if mass < 0 {
+            return Err(FuelError::NegativeMass(mass));
+        }
+        let fuel = self
+            .rounding
+            .divide(mass, self.divisor)
+            .checked_sub(self.subtrahend)
+            .ok_or(FuelError::Overflow(mass))?;
+        if self.floor_at_zero {
+            Ok(fuel.max(0))
+        } else {
+            Ok(fuel)
+        }
+    }
+
+    /// The fuel for the mass, followed by the fuel for that fuel and so on,
+    /// until no more fuel is needed. The chain ends before the first amount
+    /// that is not positive.
+    pub fn fuel_chain(&self, mass: i64) -> Result<Vec<i64>, FuelError> {
+        let mut chain = Vec::new();
+        let mut current = mass;
+        let mut increment = self.fuel(mass)?;
+        while increment > 0 {
+            if increment >= current {
+                return Err(FuelError::Diverges(mass));
+            }
+            chain.push(increment);
+            current = increment;
+            increment = self.fuel(increment)?;
+        }
+        Ok(chain)
+    }
+
+    /// The fuel for the mass including the fuel for the fuel. This walks the
+    /// chain in a loop, so long chains cannot exhaust the stack.
+    pub fn fuel_recursive(&self, mass: i64) -> Result<i64, FuelError> {
+        let mut total: i64 = 0;
+        let mut current = mass;
+        loop {
+            let increment = self.fuel(current)?;
+            if increment <= 0 {
+                return Ok(total);
+            }
+            if increment >= current {
+                return Err(FuelError::Diverges(mass));
+            }
+            total = total
+                .checked_add(increment)
+                .ok_or(FuelError::Overflow(mass))?;
+            current = increment;
+        }
+    }
+}
+
+/// Remembers the recursive fuel of every mass it has seen. Fuel chains of
+/// different modules often end in the same amounts, so sharing a memo across
+/// modules saves walking those parts again.
+#[derive(Debug)]
+pub struct FuelMemo {
+    model: FuelModel,
+    totals: HashMap<i64, i64, BuildHasherDefault<MassHasher>>,
+    pending: Vec<(i64, i64)>,
+}
+
+/// A multiplicative hasher for masses. The default SipHash costs more than
+/// walking a short fuel chain, which would defeat the purpose of the memo.
+#[derive(Debug, Default)]
+struct MassHasher(u64);
+
+impl Hasher for MassHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.write_u64(byte as u64);
+        }
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.0 = (self.0.rotate_left(5) ^ n).wrapping_mul(0x517c_c1b7_2722_0a95);
+    }
+
+    fn write_i64(&mut self, n: i64) {
+        self.write_u64(n as u64);
     }
 }
 
-pub fn calculate_fuel_recursive(mass: i64) -> i64 {
-    let increment = calculate_fuel(mass);
-    if increment == 0 {
-        increment
-    } else {
-        increment + calculate_fuel_recursive(increment)
+impl FuelMemo {
+    pub fn new(model: FuelModel) -> FuelMemo {
+        FuelMemo {
+            model,
+            totals: HashMap::default(),
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.totals.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.totals.is_empty()
+    }
+
+    /// Same as `FuelModel::fuel_recursive`, but looks up and records the
+    /// totals along the chain.
+    pub fn fuel_recursive(&mut self, mass: i64) -> Result<i64, FuelError> {
+        // Walk down the chain until its end or a mass with a known total.
+        let mut pending = std::mem::take(&mut self.pending);
+        pending.clear();
+        let mut current = mass;
+        let mut total = loop {
+            if let Some(&known) = self.totals.get(&current) {
+                break known;
+            }
+            let increment = self.model.fuel(current)?;
+            if increment <= 0 {
+                break 0;
+            }
+            if increment >= current {
+                return Err(FuelError::Diverges(mass));
+            }
+            pending.push((current, increment));
+            current = increment;
+        };
+
+        // Then walk back up, recording the totals of the masses passed.
+        while let Some((pending_mass, increment)) = pending.pop() {
+            total = total
+                .checked_add(increment)
+                .ok_or(FuelError::Overflow(mass))?;
+            self.totals.insert(pending_mass, total);
+        }
+        self.pending = pending;
+        Ok(total)
+    }
+}
+
+pub fn calculate_fuel(mass: i64) -> Result<i64, FuelError> {
+    FuelModel::default().fuel(mass)
+}
+
+/// The fuel chain under the default fuel model, see `FuelModel::fuel_chain`.
+pub fn fuel_chain(mass: i64) -> Result<Vec<i64>, FuelError> {
+    FuelModel::default().fuel_chain(mass)
+}
+
+pub fn calculate_fuel_recursive(mass: i64) -> Result<i64, FuelError> {
+    FuelModel::default().fuel_recursive(mass)
 }
 
 #[cfg(test)]
 mod advent1_tests {
 
     mod calculate_fuel_tests {
-        use super::super::calculate_fuel;
+        use super::super::{calculate_fuel, FuelError};
 
         #[test]
         fn it_works_as_expected() {
             for i in 0..6 {
-                assert_eq!(calculate_fuel(i), 0);
+                assert_eq!(calculate_fuel(i), Ok(0));
             }
-            assert_eq!(calculate_fuel(12), 2);
-            assert_eq!(calculate_fuel(14), 2);
-            assert_eq!(calculate_fuel(1969), 654);
-            assert_eq!(calculate_fuel(100756), 33583);
+            assert_eq!(calculate_fuel(12), Ok(2));
+            assert_eq!(calculate_fuel(14), Ok(2));
+            assert_eq!(calculate_fuel(1969), Ok(654));
+            assert_eq!(calculate_fuel(100756), Ok(33583));
+        }
+
+        #[test]
+        fn it_complains_about_negative_masses() {
+            assert_eq!(calculate_fuel(-1), Err(FuelError::NegativeMass(-1)));
         }
     }
 
@@ -84,32 +992,481 @@ mod advent1_tests {
         fn it_works_as_expected() {
             let test_cases = vec![(14, 2), (1969, 966), (100756, 50346)];
             for (value, expected) in test_cases {
-                assert_eq!(calculate_fuel_recursive(value), expected);
+                assert_eq!(calculate_fuel_recursive(value), Ok(expected));
             }
         }
     }
 
     mod riddle1_test {
-        use super::super::super::{Riddle, Solution};
-        use super::super::Advent1Riddle1;
+        use super::super::super::{Riddle, RiddleError, Solution};
+        use super::super::{Advent1Riddle1, Input};
 
         #[test]
         fn it_works_as_expected() {
             let riddle = Advent1Riddle1::new("./data/input/1.txt");
-            let solution = riddle.solve(&vec![]).unwrap();
+            let solution = riddle.solve(&[]).unwrap();
 
             assert_eq!(solution, Solution::Number(3210097));
         }
+
+        #[test]
+        fn it_reads_inline_input() {
+            let riddle = Advent1Riddle1::new(Input::text("12\n14\n1969\n100756\n"));
+            let solution = riddle.solve(&[]).unwrap();
+
+            assert_eq!(solution, Solution::Number(34241));
+        }
+
+        #[test]
+        fn it_reports_the_first_invalid_line() {
+            let riddle = Advent1Riddle1::new("./data/test/advent1/invalid_masses.txt");
+            match riddle.solve(&[]) {
+                Err(RiddleError::Generic(message)) => assert_eq!(
+                    message,
+                    "line 2: cannot calculate the fuel: the mass -5 is negative"
+                ),
+                other => panic!("expected an error, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn it_reports_lines_that_are_not_masses_as_parse_errors() {
+            let riddle = Advent1Riddle1::new(Input::text("12\nfoo\n"));
+            match riddle.solve(&[]) {
+                Err(RiddleError::Parse(err)) => {
+                    assert_eq!(err.to_string(), "line 2: 'foo' is not a mass")
+                }
+                other => panic!("expected a parse error, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn it_skips_invalid_lines_in_lenient_mode() {
+            let riddle = Advent1Riddle1::new("./data/test/advent1/invalid_masses.txt");
+            let solution = riddle.solve(&["lenient=true".to_string()]).unwrap();
+
+            assert_eq!(solution.answer(), &Solution::Number(4));
+            assert_eq!(
+                solution.notes().unwrap().warnings,
+                vec!["skipped 2 invalid line(s) in ./data/test/advent1/invalid_masses.txt"]
+            );
+        }
+
+        #[test]
+        fn it_accepts_a_fuel_model() {
+            let riddle = Advent1Riddle1::new("./data/test/advent1/masses.txt");
+            let args: Vec<String> = vec!["divisor=2", "subtrahend=0", "rounding=ceil"]
+                .into_iter()
+                .map(String::from)
+                .collect();
+            let solution = riddle.solve(&args).unwrap();
+
+            assert_eq!(solution, Solution::Number(51376));
+        }
+
+        #[test]
+        fn it_rejects_invalid_fuel_models() {
+            let riddle = Advent1Riddle1::new("./data/input/1.txt");
+            for arg in &[
+                "divisor=0",
+                "divisor=x",
+                "rounding=up",
+                "floor-at-zero=maybe",
+                "threads=0",
+                "threads=many",
+            ] {
+                assert!(riddle.solve(&[arg.to_string()]).is_err());
+            }
+        }
+    }
+
+    mod sum_fuel_tests {
+        use super::super::{calculate_fuel, sum_fuel, FuelOptions, FuelSum, Input, ModuleError};
+
+        #[test]
+        fn it_counts_skipped_lines() {
+            let options = FuelOptions {
+                lenient: true,
+                ..FuelOptions::default()
+            };
+            let result = sum_fuel(
+                &Input::path("./data/test/advent1/invalid_masses.txt"),
+                calculate_fuel,
+                &options,
+            )
+            .unwrap();
+            assert_eq!(
+                result,
+                FuelSum {
+                    total: 4,
+                    skipped: 2
+                }
+            );
+        }
+
+        #[test]
+        fn it_names_lines_that_are_not_masses() {
+            let options = FuelOptions::default();
+            match sum_fuel(
+                &Input::path("./data/test/io/ints_from_file/no_ints.txt"),
+                calculate_fuel,
+                &options,
+            ) {
+                Err(ModuleError::Parse { line, text }) => {
+                    assert_eq!(line, 3);
+                    assert_eq!(text, "foo");
+                }
+                other => panic!("expected a parse error, got {:?}", other),
+            }
+        }
+    }
+
+    mod sum_fuel_parallel_tests {
+        use super::super::{
+            calculate_fuel, calculate_fuel_recursive, sum_fuel, sum_fuel_parallel, sum_modules,
+            FuelOptions, Input,
+        };
+
+        const THREADS: [usize; 6] = [1, 2, 3, 5, 8, 200];
+
+        fn lenient() -> FuelOptions {
+            FuelOptions {
+                lenient: true,
+                ..FuelOptions::default()
+            }
+        }
+
+        #[test]
+        fn it_sums_up_like_the_sequential_path() {
+            let path = "./data/input/1.txt";
+            let options = FuelOptions::default();
+            let expected =
+                sum_fuel(&Input::path(path), calculate_fuel_recursive, &options).unwrap();
+            for &threads in &THREADS {
+                let result =
+                    sum_fuel_parallel(path, || calculate_fuel_recursive, &options, threads)
+                        .unwrap();
+                assert_eq!(result, expected, "{} threads", threads);
+            }
+        }
+
+        #[test]
+        fn it_reports_the_same_error_as_the_sequential_path() {
+            for path in &[
+                "./data/test/advent1/invalid_masses.txt",
+                "./data/test/advent1/overflowing_masses.txt",
+                "./data/test/io/ints_from_file/no_ints.txt",
+            ] {
+                let options = FuelOptions::default();
+                let expected = sum_fuel(&Input::path(path), calculate_fuel, &options)
+                    .unwrap_err()
+                    .to_string();
+                for &threads in &THREADS {
+                    let error = sum_fuel_parallel(path, || calculate_fuel, &options, threads)
+                        .unwrap_err()
+                        .to_string();
+                    assert_eq!(error, expected, "{} with {} threads", path, threads);
+                }
+            }
+        }
+
+        #[test]
+        fn it_skips_the_same_lines_as_the_sequential_path() {
+            for path in &[
+                "./data/test/advent1/invalid_masses.txt",
+                "./data/test/advent1/overflowing_masses.txt",
+            ] {
+                let expected = sum_fuel(&Input::path(path), calculate_fuel, &lenient()).unwrap();
+                for &threads in &THREADS {
+                    let result =
+                        sum_fuel_parallel(path, || calculate_fuel, &lenient(), threads).unwrap();
+                    assert_eq!(result, expected, "{} with {} threads", path, threads);
+                }
+            }
+        }
+
+        #[test]
+        fn it_reads_compressed_files_sequentially() {
+            let path = "./data/test/io/compressed/masses.txt.gz";
+            let options = FuelOptions {
+                threads: 4,
+                ..FuelOptions::default()
+            };
+            let result = sum_modules(&Input::path(path), || calculate_fuel, &options);
+            if cfg!(feature = "compression") {
+                assert_eq!(result.unwrap().total, 3210097);
+            } else {
+                assert!(result.is_err());
+            }
+        }
+
+        #[test]
+        fn it_detects_overflows() {
+            let path = "./data/test/advent1/overflowing_masses.txt";
+            let result = sum_fuel_parallel(path, || calculate_fuel, &lenient(), 4).unwrap();
+            assert_eq!(result.total, 9223372036854775804);
+            assert_eq!(result.skipped, 1);
+        }
+    }
+
+    mod fuel_model_tests {
+        use super::super::{FuelError, FuelModel, Rounding};
+
+        #[test]
+        fn the_default_is_the_puzzle_model() {
+            let model = FuelModel {
+                divisor: 3,
+                subtrahend: 2,
+                rounding: Rounding::Floor,
+                floor_at_zero: true,
+            };
+            assert_eq!(model, FuelModel::default());
+            assert_eq!(model.fuel(1969), Ok(654));
+            assert_eq!(model.fuel_recursive(100756), Ok(50346));
+        }
+
+        #[test]
+        fn rounding_works_as_expected() {
+            let model = |rounding| FuelModel {
+                divisor: 4,
+                subtrahend: 1,
+                rounding,
+                floor_at_zero: true,
+            };
+            let test_cases = [
+                (Rounding::Floor, 10, 1),
+                (Rounding::Ceil, 10, 2),
+                (Rounding::Nearest, 10, 2),
+                (Rounding::Nearest, 9, 1),
+                (Rounding::Ceil, 8, 1),
+            ];
+            for (rounding, mass, expected) in test_cases.iter() {
+                assert_eq!(model(*rounding).fuel(*mass), Ok(*expected));
+            }
+        }
+
+        #[test]
+        fn negative_fuel_is_kept_without_floor() {
+            let model = FuelModel {
+                floor_at_zero: false,
+                ..FuelModel::default()
+            };
+            assert_eq!(model.fuel(3), Ok(-1));
+            assert_eq!(model.fuel_recursive(3), Ok(0));
+            assert_eq!(model.fuel_chain(14), Ok(vec![2]));
+        }
+
+        #[test]
+        fn it_detects_growing_fuel() {
+            let model = FuelModel {
+                divisor: 1,
+                subtrahend: -1,
+                ..FuelModel::default()
+            };
+            assert_eq!(model.fuel_recursive(10), Err(FuelError::Diverges(10)));
+            assert_eq!(model.fuel_chain(10), Err(FuelError::Diverges(10)));
+        }
+
+        #[test]
+        fn it_detects_overflow() {
+            let model = FuelModel {
+                subtrahend: i64::MIN,
+                ..FuelModel::default()
+            };
+            assert_eq!(model.fuel(9), Err(FuelError::Overflow(9)));
+        }
+    }
+
+    mod fuel_memo_tests {
+        use super::super::{FuelError, FuelMemo, FuelModel};
+
+        #[test]
+        fn it_agrees_with_the_model() {
+            let model = FuelModel::default();
+            let mut memo = FuelMemo::new(model);
+            for mass in (0..5000).chain(vec![100756, 1969, 1969, i64::MAX]) {
+                assert_eq!(memo.fuel_recursive(mass), model.fuel_recursive(mass));
+            }
+            assert!(!memo.is_empty());
+        }
+
+        #[test]
+        fn it_shares_chains_between_masses() {
+            let mut memo = FuelMemo::new(FuelModel::default());
+            memo.fuel_recursive(1969).unwrap();
+            let known = memo.len();
+            // The chain of 654 is part of the chain of 1969.
+            memo.fuel_recursive(654).unwrap();
+            assert_eq!(memo.len(), known);
+        }
+
+        #[test]
+        fn it_reports_errors() {
+            let mut memo = FuelMemo::new(FuelModel::default());
+            assert_eq!(memo.fuel_recursive(-3), Err(FuelError::NegativeMass(-3)));
+        }
+    }
+
+    mod fuel_recursive_tests {
+        use super::super::{FuelError, FuelModel};
+
+        #[test]
+        fn it_handles_long_chains() {
+            // Each step only removes one unit of mass, which would be far too
+            // deep for a recursive implementation.
+            let model = FuelModel {
+                divisor: 1,
+                subtrahend: 1,
+                ..FuelModel::default()
+            };
+            let mass = 1_000_000;
+            assert_eq!(model.fuel_recursive(mass), Ok(mass * (mass - 1) / 2));
+        }
+
+        #[test]
+        fn it_reports_overflow() {
+            let model = FuelModel {
+                divisor: 1,
+                subtrahend: 1,
+                ..FuelModel::default()
+            };
+            assert_eq!(
+                model.fuel_recursive(i64::MAX),
+                Err(FuelError::Overflow(i64::MAX))
+            );
+        }
+
+        #[test]
+        fn it_works_near_the_maximum() {
+            let total = FuelModel::default().fuel_recursive(i64::MAX).unwrap();
+            assert!(total > i64::MAX / 3 && total < i64::MAX / 2);
+        }
+    }
+
+    mod fuel_report_tests {
+        use super::super::{fuel_chain, FuelOptions, FuelReport, ModuleFuel};
+
+        fn example_report() -> FuelReport {
+            FuelReport {
+                modules: vec![
+                    ModuleFuel {
+                        line: 1,
+                        mass: 14,
+                        fuel: 2,
+                        fuel_chain: vec![2],
+                        module_total: 2,
+                        running_total: 2,
+                    },
+                    ModuleFuel {
+                        line: 2,
+                        mass: 1969,
+                        fuel: 654,
+                        fuel_chain: vec![654, 216, 70, 21, 5],
+                        module_total: 966,
+                        running_total: 968,
+                    },
+                ],
+            }
+        }
+
+        #[test]
+        fn fuel_chain_works_as_expected() {
+            assert_eq!(fuel_chain(1969), Ok(vec![654, 216, 70, 21, 5]));
+            assert_eq!(fuel_chain(5), Ok(vec![]));
+        }
+
+        #[test]
+        fn it_matches_both_riddles() {
+            let report =
+                FuelReport::from_file("./data/input/1.txt", &FuelOptions::default()).unwrap();
+            assert_eq!(report.modules.len(), 100);
+            assert_eq!(report.total_fuel(), 3210097);
+            assert_eq!(report.total_with_fuel_for_fuel(), 4812287);
+        }
+
+        #[test]
+        fn it_renders_a_table() {
+            let expected = "\
+line  mass  fuel  module total  running total  fuel chain
+   1    14     2             2              2  2
+   2  1969   654           966            968  654 + 216 + 70 + 21 + 5
+total fuel: 656, including fuel for fuel: 968";
+            assert_eq!(example_report().to_table(), expected);
+        }
+
+        #[test]
+        fn it_renders_csv() {
+            let expected = "\
+line,mass,fuel,module_total,running_total,fuel_chain
+1,14,2,2,2,2
+2,1969,654,966,968,654;216;70;21;5
+";
+            assert_eq!(example_report().to_csv(), expected);
+        }
+
+        #[test]
+        fn it_renders_json() {
+            let json = example_report().to_json().to_string();
+            assert!(json.starts_with(
+                r#"{"modules":[{"line":1,"mass":14,"fuel":2,"fuel_chain":[2],"module_total":2,"running_total":2},"#
+            ));
+            assert!(json.ends_with(r#""total_fuel":656,"total_with_fuel_for_fuel":968}"#));
+        }
     }
 
     mod riddle2_test {
         use super::super::super::{Riddle, Solution};
-        use super::super::Advent1Riddle2;
+        use super::super::{Advent1Riddle2, Input};
+        use std::io::Cursor;
 
         #[test]
         fn it_works_as_expected() {
             let riddle = Advent1Riddle2::new("./data/input/1.txt");
-            let solution = riddle.solve(&vec![]).unwrap();
+            let solution = riddle.solve(&[]).unwrap();
+
+            assert_eq!(solution, Solution::Number(4812287));
+        }
+
+        #[test]
+        fn it_reads_from_a_reader() {
+            let input = Input::reader(Cursor::new("14\n1969\n100756".as_bytes()));
+            let riddle = Advent1Riddle2::new(input);
+            let args = vec!["threads=2".to_string(), "report=csv".to_string()];
+            let solution = riddle.solve(&args).unwrap();
+
+            assert_eq!(solution.answer(), &Solution::Number(51314));
+            let report = solution.notes().unwrap().report.as_ref().unwrap();
+            assert!(report.starts_with("line,mass,fuel,"));
+            assert!(report
+                .ends_with("\n3,100756,33583,50346,51314,33583;11192;3728;1240;411;135;43;12;2"));
+        }
+
+        #[test]
+        fn it_reports_with_a_memo() {
+            let riddle = Advent1Riddle2::new("./data/input/1.txt");
+            let args = vec!["memo=true".to_string(), "report=table".to_string()];
+            let solution = riddle.solve(&args).unwrap();
+
+            assert_eq!(solution.answer(), &Solution::Number(4812287));
+            let report = solution.notes().unwrap().report.as_ref().unwrap();
+            assert!(report.ends_with("total fuel: 3210097, including fuel for fuel: 4812287"));
+        }
+
+        #[test]
+        fn it_accepts_threads() {
+            let riddle = Advent1Riddle2::new("./data/input/1.txt");
+            for args in &[vec!["threads=4"], vec!["threads=3", "memo=true"]] {
+                let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+                let solution = riddle.solve(&args).unwrap();
+
+                assert_eq!(solution, Solution::Number(4812287));
+            }
+        }
+
+        #[test]
+        fn it_accepts_a_memo() {
+            let riddle = Advent1Riddle2::new("./data/input/1.txt");
+            let solution = riddle.solve(&["memo=true".to_string()]).unwrap();
 
             assert_eq!(solution, Solution::Number(4812287));
         }
@@ -1,23 +1,43 @@
-use std::fs;
+use std::num::ParseIntError;
 use std::vec::Vec;
 
-use super::{Riddle, RiddleError, Solution};
-
-pub struct Advent2Riddle1 {
-    input_file: String,
+use super::super::io::{parse_input, Input, ParseError, Separator, Tokens};
+use super::args::{ArgKind, ArgSchema, ArgSpec, NO_ARGS};
+use super::{Day, DayRiddle, RiddleError, Solution};
+
+riddles! {
+    Advent2Riddle1 {
+        day: 2,
+        part: 1,
+        name: "1202 Program Alarm",
+        input: "2.txt",
+        args: NO_ARGS,
+    },
+    Advent2Riddle2 {
+        day: 2,
+        part: 2,
+        name: "1202 Program Alarm",
+        input: "2.txt",
+        args: TARGET_ARGS,
+    },
 }
 
-impl Advent2Riddle1 {
-    pub fn new(input_file: &str) -> Advent2Riddle1 {
-        let input_file = input_file.to_string();
-        Advent2Riddle1 { input_file }
+/// Day 2, whose input is an Intcode tape.
+pub struct Advent2;
+
+pub type Advent2Riddle1 = DayRiddle<Advent2, 1>;
+pub type Advent2Riddle2 = DayRiddle<Advent2, 2>;
+
+impl Day for Advent2 {
+    type Parsed = Vec<i64>;
+
+    fn parse(input: &Input) -> Result<Vec<i64>, RiddleError> {
+        get_numbers(input)
     }
-}
 
-impl Riddle for Advent2Riddle1 {
-    fn solve(&self, _: &[String]) -> Result<Solution, RiddleError> {
-        let numbers = get_numbers(&self.input_file)?;
-        let mut program = Program::new(numbers);
+    fn part1(numbers: &Vec<i64>, args: &[String]) -> Result<Solution, RiddleError> {
+        NO_ARGS.parse(args)?;
+        let mut program = Program::new(numbers.clone())?;
 
         let noun = 12;
         let verb = 2;
@@ -25,37 +45,15 @@ impl Riddle for Advent2Riddle1 {
 
         Ok(Solution::Number(result))
     }
-}
-
-fn get_numbers(input_file: &str) -> Result<Vec<i64>, RiddleError> {
-    let file_content = fs::read_to_string(input_file)?;
-    let numbers: Result<Vec<i64>, _> = file_content
-        .split(',')
-        .map(|s| s.trim().parse::<i64>())
-        .collect();
-    Ok(numbers.unwrap())
-}
-
-pub struct Advent2Riddle2 {
-    input_file: String,
-}
-
-impl Advent2Riddle2 {
-    pub fn new(input_file: &str) -> Advent2Riddle2 {
-        Advent2Riddle2 {
-            input_file: input_file.to_string(),
-        }
-    }
-}
-
-impl Riddle for Advent2Riddle2 {
-    fn solve(&self, _: &[String]) -> Result<Solution, RiddleError> {
-        let numbers = get_numbers(&self.input_file)?;
-        let target_result = 19_690_720;
 
+    fn part2(numbers: &Vec<i64>, args: &[String]) -> Result<Solution, RiddleError> {
+        let target_result = TARGET_ARGS
+            .parse(args)?
+            .value("target")?
+            .unwrap_or(19_690_720);
         for noun in 0..100 {
             for verb in 0..100 {
-                let mut program = Program::new(numbers.clone());
+                let mut program = Program::new(numbers.clone())?;
                 let result = program.run_with_parameters(noun, verb)?;
                 if result == target_result {
                     return Ok(Solution::Number(100 * noun + verb));
@@ -66,6 +64,31 @@ impl Riddle for Advent2Riddle2 {
     }
 }
 
+fn get_numbers(input: &Input) -> Result<Vec<i64>, RiddleError> {
+    let numbers: Vec<i64> = parse_input(input, Separator::Comma)?;
+    if numbers.is_empty() {
+        return Err(IntcodeError::EmptyProgram.into());
+    }
+    Ok(numbers)
+}
+
+/// Parses an Intcode tape from a borrowed input, for example the contents
+/// of a `MappedFile`.
+pub fn tape_from_str(text: &str, file: &str) -> Result<Vec<i64>, ParseError<ParseIntError>> {
+    Tokens::new(text, Separator::Comma)
+        .map(|token| token.parse(file))
+        .collect()
+}
+
+/// The options of the second riddle of day 2.
+pub const TARGET_ARGS: ArgSchema = ArgSchema {
+    options: &[ArgSpec {
+        name: "target",
+        kind: ArgKind::Integer,
+        help: "the output the noun and verb are searched for (default 19690720)",
+    }],
+};
+
 impl std::convert::From<IntcodeError> for RiddleError {
     fn from(err: IntcodeError) -> Self {
         RiddleError::Generic(format!("{:?}", err))
@@ -85,6 +108,7 @@ pub enum IntcodeError {
     ModifyPositionOutOfBounds(i64),
     ArgumentPositionOutOfBounds(usize, i64),
     ProgramTooShort(usize),
+    EmptyProgram,
 }
 
 #[derive(Eq, PartialEq, Debug)]
@@ -93,9 +117,11 @@ pub struct Program {
 }
 
 impl Program {
-    pub fn new(int_code: Vec<i64>) -> Program {
-        assert!(!int_code.is_empty());
-        Program { int_code }
+    pub fn new(int_code: Vec<i64>) -> Result<Program, IntcodeError> {
+        if int_code.is_empty() {
+            return Err(IntcodeError::EmptyProgram);
+        }
+        Ok(Program { int_code })
     }
 
     pub fn run_with_parameters(&mut self, noun: i64, verb: i64) -> Result<i64, IntcodeError> {
@@ -175,38 +201,113 @@ impl Program {
 #[cfg(test)]
 mod advent2_tests {
     mod riddle1_test {
-        use super::super::super::{Riddle, Solution};
+        use super::super::super::super::io::Input;
+        use super::super::super::{Riddle, RiddleError, Solution};
         use super::super::Advent2Riddle1;
 
         #[test]
         fn it_works_as_expected() {
             let riddle = Advent2Riddle1::new("./data/input/2.txt");
-            let solution = riddle.solve(&vec![]).unwrap();
+            let solution = riddle.solve(&[]).unwrap();
 
             assert_eq!(solution, Solution::Number(4023471));
         }
+
+        #[test]
+        fn it_reports_where_the_program_is_invalid() {
+            let riddle = Advent2Riddle1::new("./data/test/advent2/invalid_program.txt");
+            match riddle.solve(&[]) {
+                Err(RiddleError::Parse(err)) => match *err {
+                    RiddleError::Generic(message) => assert!(message.starts_with(
+                        "./data/test/advent2/invalid_program.txt:2:4: cannot parse 'x'"
+                    )),
+                    other => panic!("expected a generic error, got {:?}", other),
+                },
+                other => panic!("expected an error, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn it_rejects_empty_programs() {
+            let riddle = Advent2Riddle1::new(Input::text(""));
+            match riddle.solve(&[]) {
+                Err(RiddleError::Parse(err)) => {
+                    assert!(matches!(*err, RiddleError::Generic(ref m) if m == "EmptyProgram"))
+                }
+                other => panic!("expected a parse error, got {:?}", other),
+            }
+        }
+    }
+
+    mod tape_from_str_test {
+        use super::super::tape_from_str;
+
+        #[test]
+        fn it_parses_borrowed_tapes() {
+            assert_eq!(
+                tape_from_str("1,0,0,3,\n99\n", "<text>").unwrap(),
+                vec![1, 0, 0, 3, 99]
+            );
+        }
+
+        #[test]
+        fn it_reports_the_position() {
+            let err = tape_from_str("1,0,\n99,x", "<text>").unwrap_err();
+            assert_eq!(
+                (err.line, err.column, err.line_text.as_str()),
+                (2, 4, "99,x")
+            );
+        }
     }
 
     mod riddle2_test {
-        use super::super::super::{Riddle, Solution};
+        use super::super::super::{Riddle, RiddleError, Solution};
         use super::super::Advent2Riddle2;
 
         #[test]
         fn it_works_as_expected() {
             let riddle = Advent2Riddle2::new("./data/input/2.txt");
-            let solution = riddle.solve(&vec![]).unwrap();
+            let solution = riddle.solve(&[]).unwrap();
 
             assert_eq!(solution, Solution::Number(8051));
         }
+
+        #[test]
+        fn it_searches_for_the_target() {
+            let riddle = Advent2Riddle2::new("./data/input/2.txt");
+            let solution = riddle.solve(&["target=4023471".to_string()]).unwrap();
+
+            assert_eq!(solution, Solution::Number(1202));
+        }
+
+        #[test]
+        fn it_rejects_invalid_targets() {
+            let riddle = Advent2Riddle2::new("./data/input/2.txt");
+            match riddle.solve(&["target=many".to_string()]) {
+                Err(RiddleError::InvalidArguments { message, .. }) => {
+                    assert_eq!(message, "target expects <n>, got 'many'")
+                }
+                other => panic!("expected invalid arguments, got {:?}", other),
+            }
+        }
     }
 
     mod program_tests {
+        mod new_tests {
+            use super::super::super::{IntcodeError, Program};
+
+            #[test]
+            fn it_rejects_empty_programs() {
+                assert_eq!(Program::new(vec![]), Err(IntcodeError::EmptyProgram));
+            }
+        }
+
         mod run_with_parameters_tests {
             use super::super::super::Program;
 
             #[test]
             fn it_works_as_expected() {
-                let mut program = Program::new(vec![1, 0, 0, 0, 99]);
+                let mut program = Program::new(vec![1, 0, 0, 0, 99]).unwrap();
                 let result = program.run_with_parameters(4, 1).unwrap();
                 assert_eq!(result, 103);
             }
@@ -231,8 +332,8 @@ mod advent2_tests {
                     ),
                 ];
                 for (input, output) in test_cases {
-                    let mut input = Program::new(input);
-                    let output = Program::new(output);
+                    let mut input = Program::new(input).unwrap();
+                    let output = Program::new(output).unwrap();
 
                     input.run().unwrap();
                     assert_eq!(input, output);
@@ -245,30 +346,30 @@ mod advent2_tests {
 
             #[test]
             fn it_works_for_opcode_1() {
-                let mut program = Program::new(vec![1, 2, 2, 0]);
+                let mut program = Program::new(vec![1, 2, 2, 0]).unwrap();
                 let next = program.execute_step(0).unwrap();
-                assert_eq!(program, Program::new(vec![4, 2, 2, 0]));
+                assert_eq!(program, Program::new(vec![4, 2, 2, 0]).unwrap());
                 assert_eq!(next, NextAction::Proceed);
             }
 
             #[test]
             fn it_works_for_opcode_2() {
-                let mut program = Program::new(vec![2, 1, 0, 3]);
+                let mut program = Program::new(vec![2, 1, 0, 3]).unwrap();
                 let next = program.execute_step(0).unwrap();
-                assert_eq!(program, Program::new(vec![2, 1, 0, 2]));
+                assert_eq!(program, Program::new(vec![2, 1, 0, 2]).unwrap());
                 assert_eq!(next, NextAction::Proceed);
             }
 
             #[test]
             fn it_works_for_opcode_99() {
-                let mut program = Program::new(vec![99]);
+                let mut program = Program::new(vec![99]).unwrap();
                 let next = program.execute_step(0).unwrap();
                 assert_eq!(next, NextAction::Stop);
             }
 
             #[test]
             fn it_complains_for_other_opcodes() {
-                let mut program = Program::new(vec![3, 5, 10, 2]);
+                let mut program = Program::new(vec![3, 5, 10, 2]).unwrap();
                 let result = program.execute_step(0);
                 assert_eq!(result, Err(IntcodeError::UnknownOpcode(3, 0)));
             }
@@ -276,12 +377,12 @@ mod advent2_tests {
             #[test]
             fn it_complains_when_the_store_position_is_out_of_bounds() {
                 {
-                    let mut program = Program::new(vec![1, 5, 10, -1]);
+                    let mut program = Program::new(vec![1, 5, 10, -1]).unwrap();
                     let result = program.execute_step(0);
                     assert_eq!(result, Err(IntcodeError::ModifyPositionOutOfBounds(-1)));
                 }
                 {
-                    let mut program = Program::new(vec![1, 5, 10, 10]);
+                    let mut program = Program::new(vec![1, 5, 10, 10]).unwrap();
                     let result = program.execute_step(0);
                     assert_eq!(result, Err(IntcodeError::ModifyPositionOutOfBounds(10)));
                 }
@@ -289,7 +390,7 @@ mod advent2_tests {
 
             #[test]
             fn it_complains_when_the_program_position_is_out_of_bounds() {
-                let mut program = Program::new(vec![1, 5, 10, 10]);
+                let mut program = Program::new(vec![1, 5, 10, 10]).unwrap();
                 let result = program.execute_step(10);
                 assert_eq!(result, Err(IntcodeError::ProgramPositionOutOfBounds(10)));
             }
@@ -300,13 +401,13 @@ mod advent2_tests {
 
             #[test]
             fn it_works_as_expected() {
-                let program = Program::new(vec![1, 2, 3, 4, 5, 6, 7, 8]);
+                let program = Program::new(vec![1, 2, 3, 4, 5, 6, 7, 8]).unwrap();
                 assert_eq!(program.get_arg(1).unwrap(), 3);
             }
 
             #[test]
             fn it_complains_when_position_is_out_of_bounds() {
-                let program = Program::new(vec![1, 25, 3, 4, 5, 6, 7, 8]);
+                let program = Program::new(vec![1, 25, 3, 4, 5, 6, 7, 8]).unwrap();
                 assert_eq!(
                     program.get_arg(1),
                     Err(IntcodeError::ArgumentPositionOutOfBounds(1, 25))
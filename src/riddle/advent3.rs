@@ -1,89 +1,563 @@
 use std::collections::HashSet;
-use std::convert::From;
-use std::iter::{FromIterator};
+use std::convert::{From, TryFrom};
+use std::fmt;
+use std::io::BufRead;
+use std::iter::FromIterator;
 use std::num::ParseIntError;
 use std::str;
 
-use super::super::io::lines_from_file;
-use super::{Riddle, RiddleError, Solution};
+use super::super::geometry::{Direction, Grid, ParsePointError, Point, SparseGrid};
+use super::super::io::{Input, ParseError, ParseErrorKind, Separator, Tokens, Values};
+use super::args::{ArgKind, ArgSchema, ArgSpec};
+use super::{Day, DayRiddle, Notes, RiddleError, Solution};
 
-pub struct Advent3Riddle1 {
-    input_file: String,
+riddles! {
+    Advent3Riddle1 {
+        day: 3,
+        part: 1,
+        name: "Crossed Wires",
+        input: "3.txt",
+        args: DISTANCE_ARGS,
+    },
+    Advent3Riddle2 {
+        day: 3,
+        part: 2,
+        name: "Crossed Wires",
+        input: "3.txt",
+        args: DISTANCE_ARGS,
+    },
 }
 
-impl Advent3Riddle1 {
-    pub fn new(input_file: &str) -> Advent3Riddle1 {
-        Advent3Riddle1 {
-            input_file: input_file.to_string(),
+/// Day 3, whose input are the paths of two wires.
+pub struct Advent3;
+
+pub type Advent3Riddle1 = DayRiddle<Advent3, 1>;
+pub type Advent3Riddle2 = DayRiddle<Advent3, 2>;
+
+impl Day for Advent3 {
+    type Parsed = (Vec<Segment>, Vec<Segment>);
+
+    fn parse(input: &Input) -> Result<Self::Parsed, RiddleError> {
+        Ok(get_paths(input)?)
+    }
+
+    fn part1((path1, path2): &Self::Parsed, args: &[String]) -> Result<Solution, RiddleError> {
+        let options = DistanceOptions::from_args(args, Metric::Manhattan)?;
+        closest_intersection(path1, path2, &options)
+    }
+
+    fn part2((path1, path2): &Self::Parsed, args: &[String]) -> Result<Solution, RiddleError> {
+        let options = DistanceOptions::from_args(args, Metric::WireSteps)?;
+        closest_intersection(path1, path2, &options)
+    }
+}
+
+/// The options both riddles of day 3 accept: `metric=<name>` selects the
+/// distance metric and `origin=<x>,<y>` the point distances are measured from.
+/// With `draw=true`, the solution notes a drawing of both wires.
+#[derive(Debug, PartialEq, Eq)]
+struct DistanceOptions {
+    metric: Metric,
+    origin: Point,
+    draw: bool,
+}
+
+/// The options of both riddles of day 3, see `DistanceOptions`.
+pub const DISTANCE_ARGS: ArgSchema = ArgSchema {
+    options: &[
+        ArgSpec {
+            name: "metric",
+            kind: ArgKind::Choice(&["manhattan", "chebyshev", "euclidean-squared", "wire-steps"]),
+            help: "measures the distance to an intersection",
+        },
+        ArgSpec {
+            name: "origin",
+            kind: ArgKind::Text("<x>,<y>"),
+            help: "the point distances are measured from (default 0,0)",
+        },
+        ArgSpec {
+            name: "draw",
+            kind: ArgKind::Bool,
+            help: "draws both wires with the answer",
+        },
+    ],
+};
+
+impl DistanceOptions {
+    fn from_args(args: &[String], default_metric: Metric) -> Result<DistanceOptions, RiddleError> {
+        let args = DISTANCE_ARGS.parse(args)?;
+        Ok(DistanceOptions {
+            metric: args.value("metric")?.unwrap_or(default_metric),
+            origin: args.value("origin")?.unwrap_or_else(Point::origin),
+            draw: args.flag("draw"),
+        })
+    }
+}
+
+fn closest_intersection(
+    path1: &[Segment],
+    path2: &[Segment],
+    options: &DistanceOptions,
+) -> Result<Solution, RiddleError> {
+    let in_both_parts = points_in_both_paths(path1, path2)?;
+
+    let mut min_distance: Option<(usize, Point)> = None;
+    for point in in_both_parts {
+        let distance = options
+            .metric
+            .distance(&options.origin, &point, path1, path2)?
+            .ok_or(RiddleError::NoSolutionFound)?;
+        if min_distance.map_or(true, |(min, _)| distance < min) {
+            min_distance = Some((distance, point));
         }
     }
+
+    let (distance, point) = min_distance.ok_or(RiddleError::NoSolutionFound)?;
+    let distance = i64::try_from(distance).map_err(|_| WireError::DistanceOverflow(point))?;
+    let mut notes = Notes::default();
+    if options.draw {
+        notes.report = Some(draw_wires(path1, path2)?.to_string());
+    }
+    Ok(Solution::Number(distance).with_notes(notes))
 }
 
-impl Riddle for Advent3Riddle1 {
-    fn solve(&self, _: &[String]) -> Result<Solution, RiddleError> {
-        let (path1, path2) = get_paths_from_file(&self.input_file);
-        let in_both_parts = points_in_both_paths(&path1, &path2);
+/// Draws both wires with north at the top. Each wire shows `-`, `|`, `/`
+/// and `\` for the directions of its segments, `*` for relative segments and
+/// `+` where it crosses itself on `.`. `X` marks where the wires cross and
+/// `o` the origin. Relative segments only show the lattice points they pass.
+pub fn draw_wires(path1: &[Segment], path2: &[Segment]) -> Result<Grid<char>, WireError> {
+    let wire1 = draw_wire(path1).map_err(|err| err.on_wire(0))?;
+    let mut drawing = draw_wire(path2).map_err(|err| err.on_wire(1))?;
+    for (point, symbol) in wire1.iter() {
+        let symbol = if drawing.get(point).is_some() {
+            'X'
+        } else {
+            *symbol
+        };
+        drawing.insert(*point, symbol);
+    }
+    drawing.insert(Point::origin(), 'o');
+    drawing.to_grid('.').ok_or(WireError::TooLargeToDraw)
+}
 
-        let min_distance = in_both_parts.iter().map(|p| p.manhattan_norm()).min();
+/// The points of a wire with the symbols of its segments.
+fn draw_wire(path: &[Segment]) -> Result<SparseGrid<char>, WireError> {
+    let mut drawing = SparseGrid::new();
+    let mut points = Point::origin().points_in_path(path)?.into_iter();
+    for segment in path {
+        for point in points.by_ref().take(segment.len()) {
+            let symbol = match drawing.get(&point) {
+                Some(_) => '+',
+                None => segment.symbol(),
+            };
+            drawing.insert(point, symbol);
+        }
+    }
+    Ok(drawing)
+}
 
-        min_distance
-            .map(|n| Solution::Number(n as i64))
-            .ok_or_else(|| RiddleError::Generic("could not determine min".to_string()))
+fn get_paths(input: &Input) -> Result<(Vec<Segment>, Vec<Segment>), WireError> {
+    let wires: Result<Vec<_>, _> = WireReader::new(input.open()?, input.name()).collect();
+    let mut wires = wires?;
+    if wires.len() != 2 {
+        return Err(WireError::WireCount(wires.len()));
     }
+
+    let path2 = wires.pop().unwrap();
+    let path1 = wires.pop().unwrap();
+    Ok((path1, path2))
 }
 
-pub struct Advent3Riddle2 {
-    input_file: String,
+/// The points both wires pass. Distances are only measured to lattice
+/// points, so wires that cross between them fail with
+/// `WireError::OffLatticeCrossing`.
+fn points_in_both_paths(path1: &[Segment], path2: &[Segment]) -> Result<HashSet<Point>, WireError> {
+    check_lattice_crossings(path1, path2)?;
+    let origin = Point::origin();
+    let points1 = origin
+        .point_set_in_path(path1)
+        .map_err(|err| err.on_wire(0))?;
+    let points2 = origin
+        .point_set_in_path(path2)
+        .map_err(|err| err.on_wire(1))?;
+    let intersection: HashSet<_> = points1.intersection(&points2).copied().collect();
+    Ok(intersection)
 }
 
-impl Advent3Riddle2 {
-    pub fn new(input_file: &str) -> Advent3Riddle2 {
-        Advent3Riddle2 {
-            input_file: input_file.to_string(),
+/// Fails if a segment of one wire crosses a segment of the other between
+/// lattice points. Segments step along primitive vectors, so two of them can
+/// only cross there if the cross product of their steps is at least 2, as
+/// for two diagonals. Wires of only `R`, `L`, `U` and `D` never do, so they
+/// are not swept at all.
+fn check_lattice_crossings(path1: &[Segment], path2: &[Segment]) -> Result<(), WireError> {
+    let spans1 = segment_spans(path1).map_err(|err| err.on_wire(0))?;
+    let spans2 = segment_spans(path2).map_err(|err| err.on_wire(1))?;
+    let (steps1, steps2) = (StepKinds::of(&spans1), StepKinds::of(&spans2));
+    let mut spans: Vec<(usize, Span)> = spans1
+        .into_iter()
+        .filter(|span| steps2.may_cross(&span.step))
+        .map(|span| (0, span))
+        .chain(
+            spans2
+                .into_iter()
+                .filter(|span| steps1.may_cross(&span.step))
+                .map(|span| (1, span)),
+        )
+        .collect();
+    if !spans.iter().any(|(wire, _)| *wire == 0) || !spans.iter().any(|(wire, _)| *wire == 1) {
+        return Ok(());
+    }
+
+    // Sweep from left to right, keeping the segments of each wire that
+    // reach the current x, and only compare segments whose boxes overlap.
+    spans.sort_by_key(|(_, span)| span.min.x);
+    let mut active: [Vec<&Span>; 2] = [Vec::new(), Vec::new()];
+    for (wire, span) in &spans {
+        for spans in active.iter_mut() {
+            spans.retain(|other| other.max.x >= span.min.x);
         }
+        for other in &active[1 - wire] {
+            if other.max.y < span.min.y || span.max.y < other.min.y {
+                continue;
+            }
+            let (span1, span2) = if *wire == 0 {
+                (span, *other)
+            } else {
+                (*other, span)
+            };
+            let (segment1, segment2) = (span1.index, span2.index);
+            let crossing = off_lattice_crossing(
+                (&span1.start, &span1.step, span1.len),
+                (&span2.start, &span2.step, span2.len),
+            )
+            .ok_or(WireError::CrossingOverflow { segment1, segment2 })?;
+            if crossing {
+                return Err(WireError::OffLatticeCrossing { segment1, segment2 });
+            }
+        }
+        active[*wire].push(span);
     }
+    Ok(())
 }
 
-impl Riddle for Advent3Riddle2 {
-    fn solve(&self, _: &[String]) -> Result<Solution, RiddleError> {
-        let (path1, path2) = get_paths_from_file(&self.input_file);
-        let in_both_parts = points_in_both_paths(&path1, &path2);
+/// A segment of a wire with where it starts, the step it takes and its box.
+struct Span {
+    index: usize,
+    start: Point,
+    step: Point,
+    len: usize,
+    min: Point,
+    max: Point,
+}
+
+/// The spans of the segments of the path.
+fn segment_spans(path: &[Segment]) -> Result<Vec<Span>, WireError> {
+    let mut start = Point::origin();
+    let mut spans = Vec::with_capacity(path.len());
+    for (segment_index, segment) in path.iter().enumerate() {
+        let end =
+            segment
+                .checked_point_after(&start, segment.len())
+                .ok_or(WireError::Overflow {
+                    wire: None,
+                    segment_index,
+                    segment: *segment,
+                })?;
+        spans.push(Span {
+            index: segment_index,
+            start,
+            step: segment.step(),
+            len: segment.len(),
+            min: Point::new(start.x.min(end.x), start.y.min(end.y)),
+            max: Point::new(start.x.max(end.x), start.y.max(end.y)),
+        });
+        start = end;
+    }
+    Ok(spans)
+}
 
-        let origin = Point{ x:0, y:0 };
-        let signal_distances: Vec<_> = in_both_parts
-            .iter()
-            .map(|point| {
-                let index_path_1 = origin.index_of_point_in_path(point, &path1).unwrap();
-                let index_path_2 = origin.index_of_point_in_path(point, &path2).unwrap();
-                index_path_1 + index_path_2
-            })
-            .collect();
+/// What the steps of a wire look like, enough to tell which steps of the
+/// other wire may cross it between lattice points.
+struct StepKinds {
+    /// Whether any step is not along an axis.
+    off_axis: bool,
+    /// Whether any step moves more than one in x, or in y.
+    steep_x: bool,
+    steep_y: bool,
+}
 
-        Ok(Solution::Number(*signal_distances.iter().min().unwrap() as i64))
+impl StepKinds {
+    fn of(spans: &[Span]) -> StepKinds {
+        let steps = || spans.iter().map(|span| span.step);
+        StepKinds {
+            off_axis: steps().any(|step| step.x != 0 && step.y != 0),
+            steep_x: steps().any(|step| step.x.unsigned_abs() > 1),
+            steep_y: steps().any(|step| step.y.unsigned_abs() > 1),
+        }
     }
+
+    /// Whether `step` may cross a step of these kinds between lattice
+    /// points. An axis step only does so with a step that moves more than
+    /// one across it, and a diagonal never does with an axis step.
+    fn may_cross(&self, step: &Point) -> bool {
+        match (step.x.unsigned_abs(), step.y.unsigned_abs()) {
+            (_, 0) => self.steep_y,
+            (0, _) => self.steep_x,
+            (1, 1) => self.off_axis,
+            _ => true,
+        }
+    }
+}
+
+/// Whether two segments, each given by its start, its step and the number
+/// of steps, cross between lattice points. Returns `None` if that cannot be
+/// computed in range.
+fn off_lattice_crossing(
+    (a, p, k1): (&Point, &Point, usize),
+    (c, q, k2): (&Point, &Point, usize),
+) -> Option<bool> {
+    let cross = |x1: i128, y1: i128, x2: i128, y2: i128| {
+        x1.checked_mul(y2)?.checked_sub(y1.checked_mul(x2)?)
+    };
+    let (px, py, qx, qy) = (p.x as i128, p.y as i128, q.x as i128, q.y as i128);
+    let mut denominator = cross(px, py, qx, qy)?;
+    if denominator.abs() < 2 {
+        // Parallel segments share whole steps, and steps whose cross
+        // product is 1 always meet at a lattice point.
+        return Some(false);
+    }
+    let (wx, wy) = (c.x as i128 - a.x as i128, c.y as i128 - a.y as i128);
+    // The segments meet at a + s * p = c + t * q with s and t as fractions.
+    let mut s = cross(wx, wy, qx, qy)?;
+    let mut t = cross(wx, wy, px, py)?;
+    if denominator < 0 {
+        denominator = -denominator;
+        s = -s;
+        t = -t;
+    }
+    // The steps are primitive, so the point is a lattice point exactly when
+    // s is a whole number, and then so is t.
+    Some(
+        s % denominator != 0
+            && s > 0
+            && t > 0
+            && s / denominator < k1 as i128
+            && t / denominator < k2 as i128,
+    )
 }
 
-fn get_paths_from_file(filename: &String) -> (Vec<Segment>, Vec<Segment>) {
-    let lines_result: Result<Vec<_>, _> =
-        lines_from_file(filename).unwrap().collect();
-    let lines = lines_result.unwrap();
-    assert_eq!(lines.len(), 2);
+/// Parses a single wire given as comma-separated segments.
+pub fn from_comma_separated_str(input: &str) -> Result<Vec<Segment>, SegmentParseError> {
+    Separator::Comma
+        .split(input)
+        .map(|(_, s)| s.parse::<Segment>())
+        .collect()
+}
+
+/// Parses wires from a borrowed input, one wire per line, for example the
+/// contents of a `MappedFile`. Blank lines are skipped. `file` names the
+/// input in errors.
+pub fn wires_from_str(text: &str, file: &str) -> Result<Vec<Vec<Segment>>, WireError> {
+    let mut wires: Vec<Vec<Segment>> = Vec::new();
+    let mut line = 0;
+    for token in Tokens::new(text, Separator::Comma) {
+        let segment = token.parse::<Segment>(file)?;
+        match wires.last_mut() {
+            Some(wire) if token.line == line => wire.push(segment),
+            _ => {
+                wires.push(vec![segment]);
+                line = token.line;
+            }
+        }
+    }
+    Ok(wires)
+}
 
-    let path1 = from_comma_separated_str(&lines[0]).unwrap();
-    let path2 = from_comma_separated_str(&lines[1]).unwrap();
-    (path1, path2)
+/// Reads wires line by line, one wire per line. Blank lines are skipped.
+pub struct WireReader<R> {
+    values: Values<R, Segment>,
 }
 
-fn points_in_both_paths(path1: &Vec<Segment>, path2: &Vec<Segment>) -> HashSet<Point> {
-    let origin = Point { x: 0, y: 0 };
-    let points1 = origin.point_set_in_path(path1);
-    let points2 = origin.point_set_in_path(path2);
-    let intersection: HashSet<_> = points1.intersection(&points2).cloned().collect();
-    intersection
+impl<R: BufRead> WireReader<R> {
+    /// `file` names the input in errors.
+    pub fn new(reader: R, file: &str) -> WireReader<R> {
+        WireReader {
+            values: Values::new(reader, file, Separator::Comma),
+        }
+    }
+
+    /// Reads the next wire and hands each of its segments to `visit` as soon
+    /// as it is parsed. Returns `false` once the input is exhausted.
+    pub fn read_wire<F: FnMut(Segment)>(&mut self, visit: F) -> Result<bool, WireError> {
+        Ok(self.values.read_line(visit)?)
+    }
+}
+
+impl<R: BufRead> Iterator for WireReader<R> {
+    type Item = Result<Vec<Segment>, WireError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut wire = Vec::new();
+        match self.read_wire(|segment| wire.push(segment)) {
+            Ok(true) => Some(Ok(wire)),
+            Ok(false) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum WireError {
+    IO(std::io::Error),
+    Segment {
+        file: String,
+        line: usize,
+        column: usize,
+        token: String,
+        error: SegmentParseError,
+    },
+    WireCount(usize),
+    /// Walking the segment with the given index would leave the range of
+    /// `i64` coordinates. `wire` is the index of the wire, once it is known.
+    Overflow {
+        wire: Option<usize>,
+        segment_index: usize,
+        segment: Segment,
+    },
+    /// Walking the segment with the given index would take the wire past
+    /// `MAX_PATH_STEPS` steps. `wire` is the index of the wire, once it is
+    /// known.
+    TooLong {
+        wire: Option<usize>,
+        segment_index: usize,
+        segment: Segment,
+    },
+    /// The distance to the point does not fit into the result.
+    DistanceOverflow(Point),
+    /// The segments with the given indices cross between lattice points,
+    /// where distances are not measured.
+    OffLatticeCrossing {
+        segment1: usize,
+        segment2: usize,
+    },
+    /// The wires span more cells than a drawing may have.
+    TooLargeToDraw,
+    /// Where the segments with the given indices cross cannot be computed in
+    /// range.
+    CrossingOverflow {
+        segment1: usize,
+        segment2: usize,
+    },
+}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WireError::IO(err) => write!(f, "could not read wires: {}", err),
+            WireError::Segment {
+                file,
+                line,
+                column,
+                token,
+                error,
+            } => write!(
+                f,
+                "{}:{}:{}: invalid segment '{}': {:?}",
+                file, line, column, token, error
+            ),
+            WireError::WireCount(n) => write!(f, "expected 2 wires, found {}", n),
+            WireError::Overflow {
+                wire,
+                segment_index,
+                segment,
+            } => write!(
+                f,
+                "segment {} ({:?}){} leaves the coordinate range",
+                segment_index + 1,
+                segment,
+                of_wire(*wire)
+            ),
+            WireError::TooLong {
+                wire,
+                segment_index,
+                segment,
+            } => write!(
+                f,
+                "segment {} ({:?}){} makes the wire longer than {} steps",
+                segment_index + 1,
+                segment,
+                of_wire(*wire),
+                MAX_PATH_STEPS
+            ),
+            WireError::DistanceOverflow(point) => {
+                write!(f, "the distance to ({}, {}) overflows", point.x, point.y)
+            }
+            WireError::OffLatticeCrossing { segment1, segment2 } => write!(
+                f,
+                "segment {} of the first wire crosses segment {} of the second wire between lattice points",
+                segment1 + 1,
+                segment2 + 1
+            ),
+            WireError::TooLargeToDraw => write!(f, "the wires are too large to draw"),
+            WireError::CrossingOverflow { segment1, segment2 } => write!(
+                f,
+                "cannot tell where segment {} of the first wire and segment {} of the second wire cross",
+                segment1 + 1,
+                segment2 + 1
+            ),
+        }
+    }
+}
+
+/// Names the wire for messages about one of its segments.
+fn of_wire(wire: Option<usize>) -> String {
+    match wire {
+        None => String::new(),
+        Some(0) => " of the first wire".to_string(),
+        Some(1) => " of the second wire".to_string(),
+        Some(wire) => format!(" of wire {}", wire + 1),
+    }
+}
+
+impl WireError {
+    /// Records which wire an error from walking a path happened on.
+    fn on_wire(mut self, index: usize) -> WireError {
+        if let WireError::Overflow { wire, .. } | WireError::TooLong { wire, .. } = &mut self {
+            *wire = Some(index);
+        }
+        self
+    }
+}
+
+impl From<std::io::Error> for WireError {
+    fn from(err: std::io::Error) -> Self {
+        WireError::IO(err)
+    }
+}
+
+impl From<ParseError<SegmentParseError>> for WireError {
+    fn from(err: ParseError<SegmentParseError>) -> Self {
+        match err.kind {
+            ParseErrorKind::Value { text, error } => WireError::Segment {
+                file: err.file,
+                line: err.line,
+                column: err.column,
+                token: text,
+                error,
+            },
+            ParseErrorKind::IO(err) => WireError::IO(err),
+            ParseErrorKind::RaggedRow { .. } => unreachable!("wires are not grids"),
+        }
+    }
 }
 
-fn from_comma_separated_str(input: &str) -> Result<Vec<Segment>, SegmentParseError> {
-    input.split(',').map(|s| s.parse::<Segment>()).collect()
+impl From<WireError> for RiddleError {
+    fn from(err: WireError) -> Self {
+        match err {
+            WireError::IO(err) => RiddleError::IO(err),
+            err => RiddleError::Generic(err.to_string()),
+        }
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
@@ -92,17 +566,90 @@ pub enum Segment {
     L(usize),
     U(usize),
     D(usize),
+    NE(usize),
+    NW(usize),
+    SE(usize),
+    SW(usize),
+    /// A segment given as `(dx,dy)`. The wire only passes the lattice points
+    /// on the line, so it takes `gcd(|dx|, |dy|)` steps.
+    Relative(i64, i64),
 }
 
 impl Segment {
+    /// The number of steps the segment takes.
     fn len(&self) -> usize {
         match self {
             Segment::U(n) => *n,
             Segment::D(n) => *n,
             Segment::L(n) => *n,
             Segment::R(n) => *n,
+            Segment::NE(n) => *n,
+            Segment::NW(n) => *n,
+            Segment::SE(n) => *n,
+            Segment::SW(n) => *n,
+            Segment::Relative(dx, dy) => gcd(dx.unsigned_abs(), dy.unsigned_abs()) as usize,
         }
     }
+
+    /// The direction of the segment, unless it is given relatively.
+    pub fn direction(&self) -> Option<Direction> {
+        match self {
+            Segment::U(_) => Some(Direction::North),
+            Segment::D(_) => Some(Direction::South),
+            Segment::L(_) => Some(Direction::West),
+            Segment::R(_) => Some(Direction::East),
+            Segment::NE(_) => Some(Direction::NorthEast),
+            Segment::NW(_) => Some(Direction::NorthWest),
+            Segment::SE(_) => Some(Direction::SouthEast),
+            Segment::SW(_) => Some(Direction::SouthWest),
+            Segment::Relative(_, _) => None,
+        }
+    }
+
+    /// How the segment is drawn by `draw_wires`.
+    fn symbol(&self) -> char {
+        match self.direction() {
+            Some(Direction::East) | Some(Direction::West) => '-',
+            Some(Direction::North) | Some(Direction::South) => '|',
+            Some(Direction::NorthEast) | Some(Direction::SouthWest) => '/',
+            Some(Direction::NorthWest) | Some(Direction::SouthEast) => '\\',
+            None => '*',
+        }
+    }
+
+    /// The offset a single step along this segment moves.
+    fn step(&self) -> Point {
+        match (self.direction(), self) {
+            (Some(direction), _) => direction.delta(),
+            (None, Segment::Relative(dx, dy)) => {
+                // The number of steps can be 2^63, which does not fit an i64.
+                let steps = self.len() as i128;
+                Point::new((*dx as i128 / steps) as i64, (*dy as i128 / steps) as i64)
+            }
+            (None, _) => unreachable!("only relative segments lack a direction"),
+        }
+    }
+
+    /// The point `steps` steps along the segment from `start`, or `None` if
+    /// it lies outside the range of `i64` coordinates.
+    fn checked_point_after(&self, start: &Point, steps: usize) -> Option<Point> {
+        let step = self.step();
+        let coordinate = |start: i64, step: i64| {
+            i64::try_from(start as i128 + step as i128 * steps as i128).ok()
+        };
+        Some(Point::new(
+            coordinate(start.x, step.x)?,
+            coordinate(start.y, step.y)?,
+        ))
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -111,6 +658,7 @@ pub enum SegmentParseError {
     UnknownDirectoion(String),
     LengthParseError(ParseIntError),
     LengthZero(String),
+    MalformedRelative(String),
 }
 
 impl From<ParseIntError> for SegmentParseError {
@@ -123,81 +671,218 @@ impl str::FromStr for Segment {
     type Err = SegmentParseError;
 
     fn from_str(s: &str) -> std::result::Result<Self, SegmentParseError> {
-        let s = s.to_string();
+        if s.starts_with('(') {
+            return parse_relative_segment(s);
+        }
         if s.len() < 2 {
-            return Err(SegmentParseError::TooShort(s));
+            return Err(SegmentParseError::TooShort(s.to_string()));
         }
 
-        let direction = s.chars().next().unwrap();
-        let length = s[1..].parse::<usize>()?;
+        let direction_end = s
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(s.len());
+        let (direction, length) = s.split_at(direction_end);
+        let length = length.parse::<usize>()?;
         if length == 0 {
-            return Err(SegmentParseError::LengthZero(s));
+            return Err(SegmentParseError::LengthZero(s.to_string()));
         }
 
         match direction {
-            'U' => Ok(Segment::U(length)),
-            'D' => Ok(Segment::D(length)),
-            'L' => Ok(Segment::L(length)),
-            'R' => Ok(Segment::R(length)),
-            _ => Err(SegmentParseError::UnknownDirectoion(s)),
+            "U" => Ok(Segment::U(length)),
+            "D" => Ok(Segment::D(length)),
+            "L" => Ok(Segment::L(length)),
+            "R" => Ok(Segment::R(length)),
+            "NE" => Ok(Segment::NE(length)),
+            "NW" => Ok(Segment::NW(length)),
+            "SE" => Ok(Segment::SE(length)),
+            "SW" => Ok(Segment::SW(length)),
+            _ => Err(SegmentParseError::UnknownDirectoion(s.to_string())),
         }
     }
 }
 
-#[derive(PartialEq, Eq, Debug, Hash, Clone)]
-pub struct Point {
-    pub x: i64,
-    pub y: i64,
+fn parse_relative_segment(s: &str) -> Result<Segment, SegmentParseError> {
+    let malformed = || SegmentParseError::MalformedRelative(s.to_string());
+    let inner = s
+        .strip_prefix('(')
+        .and_then(|s| s.strip_suffix(')'))
+        .ok_or_else(malformed)?;
+    let mut offsets = inner.split(',');
+    let (dx, dy) = match (offsets.next(), offsets.next(), offsets.next()) {
+        (Some(dx), Some(dy), None) => (dx.trim().parse::<i64>()?, dy.trim().parse::<i64>()?),
+        _ => return Err(malformed()),
+    };
+    if dx == 0 && dy == 0 {
+        return Err(SegmentParseError::LengthZero(s.to_string()));
+    }
+    Ok(Segment::Relative(dx, dy))
 }
 
-impl Point {
-    pub fn manhattan_norm(&self) -> usize {
-        (self.x.abs() as usize) + (self.y.abs() as usize)
-    }
+/// A way of measuring how far an intersection of the two wires is away from a
+/// reference point.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Metric {
+    Manhattan,
+    Chebyshev,
+    EuclideanSquared,
+    /// The combined number of steps both wires need to reach the point. It is
+    /// measured along the wires from where they start, so the reference point
+    /// does not matter for this metric.
+    WireSteps,
+}
 
-    pub fn points_in_segment(&self, segment: Segment) -> Vec<Point> {
-        let (x_increment, y_increment): (i64, i64) = match segment {
-            Segment::U(_) => (0, 1),
-            Segment::D(_) => (0, -1),
-            Segment::L(_) => (-1, 0),
-            Segment::R(_) => (1, 0),
+impl Metric {
+    /// Measures the distance of `point` from `reference`. Returns `None` if
+    /// the point is not on both wires and the metric needs it to be.
+    pub fn distance(
+        self,
+        reference: &Point,
+        point: &Point,
+        path1: &[Segment],
+        path2: &[Segment],
+    ) -> Result<Option<usize>, WireError> {
+        let overflow = || WireError::DistanceOverflow(*point);
+        let offset = || {
+            let x = point.x.checked_sub(reference.x)?;
+            let y = point.y.checked_sub(reference.y)?;
+            Some(Point::new(x, y))
         };
-
-        let num_steps = segment.len() + 1;
-        (1..num_steps)
-            .map(|i| {
-                let i = i as i64;
-                Point {
-                    x: self.x + i * x_increment,
-                    y: self.y + i * y_increment,
+        let distance = match self {
+            Metric::Manhattan => offset().and_then(|o| o.manhattan_norm()),
+            Metric::Chebyshev => offset().map(|o| o.chebyshev_norm()),
+            Metric::EuclideanSquared => offset().and_then(|o| o.euclidean_norm_squared()),
+            Metric::WireSteps => {
+                let start = Point::origin();
+                let steps1 = start
+                    .index_of_point_in_path(point, path1)
+                    .map_err(|err| err.on_wire(0))?;
+                let steps2 = start
+                    .index_of_point_in_path(point, path2)
+                    .map_err(|err| err.on_wire(1))?;
+                match (steps1, steps2) {
+                    (Some(steps1), Some(steps2)) => steps1.checked_add(steps2),
+                    _ => return Ok(None),
                 }
-            })
-            .collect()
+            }
+        };
+        distance.map(Some).ok_or_else(overflow)
     }
+}
+
+impl str::FromStr for Metric {
+    type Err = RiddleError;
 
-    pub fn point_set_in_path(&self, path: &Vec<Segment>) -> HashSet<Point> {
-        let points = self.points_in_path(path);
-        HashSet::from_iter(points)
+    fn from_str(s: &str) -> Result<Self, RiddleError> {
+        match s {
+            "manhattan" => Ok(Metric::Manhattan),
+            "chebyshev" => Ok(Metric::Chebyshev),
+            "euclidean-squared" => Ok(Metric::EuclideanSquared),
+            "wire-steps" => Ok(Metric::WireSteps),
+            _ => Err(RiddleError::Generic(format!("unknown metric '{}'", s))),
+        }
     }
+}
 
-    pub fn index_of_point_in_path(&self, point: &Self, path: &Vec<Segment>) -> Option<usize> {
-        for (i, p) in self.points_in_path(path).iter().enumerate() {
+impl From<ParsePointError> for RiddleError {
+    fn from(err: ParsePointError) -> Self {
+        RiddleError::Generic(format!("expected a point like '3,-4', got '{}'", err.0))
+    }
+}
+
+/// The most steps a wire may take, so that walking it cannot exhaust memory.
+pub const MAX_PATH_STEPS: usize = 10_000_000;
+
+/// Walking along wires, starting from a point. Walking fails with
+/// `WireError::Overflow` if a segment leaves the range of `i64` coordinates,
+/// and with `WireError::TooLong` if a path takes more than `MAX_PATH_STEPS`
+/// steps.
+pub trait WireWalker {
+    /// The points visited by the segment, or `None` if it overflows.
+    fn points_in_segment(&self, segment: Segment) -> Option<SegmentPoints>;
+    fn point_set_in_path(&self, path: &[Segment]) -> Result<HashSet<Point>, WireError>;
+    fn index_of_point_in_path(
+        &self,
+        point: &Point,
+        path: &[Segment],
+    ) -> Result<Option<usize>, WireError>;
+    fn points_in_path(&self, path: &[Segment]) -> Result<Vec<Point>, WireError>;
+}
+
+/// The points of a segment after its start, walked one at a time.
+#[derive(Debug, Clone)]
+pub struct SegmentPoints {
+    start: Point,
+    segment: Segment,
+    steps: std::ops::RangeInclusive<usize>,
+}
+
+impl Iterator for SegmentPoints {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Point> {
+        let steps = self.steps.next()?;
+        // Once the end of the segment is known to be in range, so are all
+        // the points before it.
+        let point = self.segment.checked_point_after(&self.start, steps);
+        Some(point.expect("the end of the segment is in range"))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.steps.size_hint()
+    }
+}
+
+impl WireWalker for Point {
+    fn points_in_segment(&self, segment: Segment) -> Option<SegmentPoints> {
+        segment.checked_point_after(self, segment.len())?;
+        Some(SegmentPoints {
+            start: *self,
+            segment,
+            steps: 1..=segment.len(),
+        })
+    }
+
+    fn point_set_in_path(&self, path: &[Segment]) -> Result<HashSet<Point>, WireError> {
+        let points = self.points_in_path(path)?;
+        Ok(HashSet::from_iter(points))
+    }
+
+    fn index_of_point_in_path(
+        &self,
+        point: &Point,
+        path: &[Segment],
+    ) -> Result<Option<usize>, WireError> {
+        for (i, p) in self.points_in_path(path)?.iter().enumerate() {
             if p == point {
-                return Some(i+1)
+                return Ok(Some(i + 1));
             }
         }
-        None
+        Ok(None)
     }
 
-    pub fn points_in_path(&self, path: &Vec<Segment>) -> Vec<Point> {
+    fn points_in_path(&self, path: &[Segment]) -> Result<Vec<Point>, WireError> {
         let mut result = Vec::new();
-        let mut start_point = self.clone();
-        for segment in path {
-            let mut segment_points = start_point.points_in_segment(*segment);
-            start_point = (*segment_points.last().unwrap()).clone();
-            result.append(&mut segment_points);
+        let mut start_point = *self;
+        for (segment_index, segment) in path.iter().enumerate() {
+            let segment_points =
+                start_point
+                    .points_in_segment(*segment)
+                    .ok_or(WireError::Overflow {
+                        wire: None,
+                        segment_index,
+                        segment: *segment,
+                    })?;
+            if segment.len() > MAX_PATH_STEPS - result.len() {
+                return Err(WireError::TooLong {
+                    wire: None,
+                    segment_index,
+                    segment: *segment,
+                });
+            }
+            result.extend(segment_points);
+            start_point = *result.last().unwrap();
         }
-        result
+        Ok(result)
     }
 }
 
@@ -205,16 +890,73 @@ impl Point {
 mod advent3_tests {
 
     mod riddle1_test {
-        use super::super::super::{Riddle, Solution};
+        use super::super::super::super::io::Input;
+        use super::super::super::{Riddle, RiddleError, Solution};
         use super::super::Advent3Riddle1;
 
         #[test]
         fn it_works_as_expected() {
             let riddle = Advent3Riddle1::new("./data/input/3.txt");
-            let solution = riddle.solve(&vec![]).unwrap();
+            let solution = riddle.solve(&[]).unwrap();
 
             assert_eq!(solution, Solution::Number(4981));
         }
+
+        #[test]
+        fn it_accepts_a_metric() {
+            let riddle = Advent3Riddle1::new("./data/input/3.txt");
+            let args = vec!["metric=wire-steps".to_string()];
+            let solution = riddle.solve(&args).unwrap();
+
+            assert_eq!(solution, Solution::Number(164012));
+        }
+
+        #[test]
+        fn it_rejects_wires_crossing_between_lattice_points() {
+            let riddle = Advent3Riddle1::new(Input::text("R1,NW1\nNE1\n"));
+            match riddle.solve(&[]) {
+                Err(RiddleError::Generic(message)) => assert_eq!(
+                    message,
+                    "segment 2 of the first wire crosses segment 1 of the second wire between lattice points"
+                ),
+                other => panic!("expected an error, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn it_draws_the_wires() {
+            let riddle = Advent3Riddle1::new(Input::text("R8,U5,L5,D3\nU7,R6,D4,L4\n"));
+            let solution = riddle.solve(&["draw=true".to_string()]).unwrap();
+            assert_eq!(solution.answer(), &Solution::Number(6));
+            let drawing = [
+                "|------..",
+                "|.....|..",
+                "|..---X-|",
+                "|..|..|.|",
+                "|.-X--|.|",
+                "|..|....|",
+                "|.......|",
+                "o--------",
+            ];
+            let report = solution.notes().unwrap().report.as_ref().unwrap();
+            assert_eq!(report, &drawing.join("\n"));
+        }
+
+        #[test]
+        fn it_draws_diagonal_and_relative_segments() {
+            let riddle = Advent3Riddle1::new(Input::text("NE2,R2,(-1,-2)\nR2,U3,R1,D2,L2\n"));
+            let solution = riddle.solve(&["draw=true".to_string()]).unwrap();
+            assert_eq!(solution.answer(), &Solution::Number(2));
+            let drawing = ["..|-.", "..XX-", ".X+|.", "o--*."];
+            let report = solution.notes().unwrap().report.as_ref().unwrap();
+            assert_eq!(report, &drawing.join("\n"));
+        }
+
+        #[test]
+        fn it_returns_an_error_for_missing_input() {
+            let riddle = Advent3Riddle1::new("./data/test/does_not_exist.txt");
+            assert!(riddle.solve(&[]).is_err());
+        }
     }
 
     mod riddle2_test {
@@ -224,12 +966,99 @@ mod advent3_tests {
         #[test]
         fn it_works_as_expected() {
             let riddle = Advent3Riddle2::new("./data/input/3.txt");
-            let solution = riddle.solve(&vec![]).unwrap();
+            let solution = riddle.solve(&[]).unwrap();
 
             assert_eq!(solution, Solution::Number(164012));
         }
     }
 
+    mod distance_options_tests {
+        use super::super::{DistanceOptions, Metric, Point};
+
+        #[test]
+        fn it_uses_the_defaults_without_arguments() {
+            let options = DistanceOptions::from_args(&[], Metric::WireSteps).unwrap();
+            let expected = DistanceOptions {
+                metric: Metric::WireSteps,
+                origin: Point { x: 0, y: 0 },
+                draw: false,
+            };
+            assert_eq!(options, expected);
+        }
+
+        #[test]
+        fn it_reads_metric_and_origin() {
+            let args = vec![
+                "metric=chebyshev".to_string(),
+                "origin=3,-4".to_string(),
+                "draw=true".to_string(),
+            ];
+            let options = DistanceOptions::from_args(&args, Metric::Manhattan).unwrap();
+            let expected = DistanceOptions {
+                metric: Metric::Chebyshev,
+                origin: Point { x: 3, y: -4 },
+                draw: true,
+            };
+            assert_eq!(options, expected);
+        }
+
+        #[test]
+        fn it_complains_about_invalid_arguments() {
+            for arg in &[
+                "metric=taxicab",
+                "origin=3",
+                "origin=a,b",
+                "colour=red",
+                "metric",
+            ] {
+                let args = vec![arg.to_string()];
+                assert!(DistanceOptions::from_args(&args, Metric::Manhattan).is_err());
+            }
+        }
+    }
+
+    mod metric_tests {
+        use super::super::{Metric, Point, Segment};
+
+        #[test]
+        fn it_measures_from_the_reference_point() {
+            let reference = Point { x: 1, y: 1 };
+            let point = Point { x: -2, y: 5 };
+            let test_cases = [
+                (Metric::Manhattan, 7),
+                (Metric::Chebyshev, 4),
+                (Metric::EuclideanSquared, 25),
+            ];
+            for (metric, expected) in test_cases.iter() {
+                assert_eq!(
+                    metric.distance(&reference, &point, &[], &[]).unwrap(),
+                    Some(*expected)
+                );
+            }
+        }
+
+        #[test]
+        fn wire_steps_are_counted_along_both_wires() {
+            let path1 = vec![Segment::R(8), Segment::U(5), Segment::L(5), Segment::D(3)];
+            let path2 = vec![Segment::U(7), Segment::R(6), Segment::D(4), Segment::L(4)];
+            let reference = Point { x: 100, y: 100 };
+            let metric = Metric::WireSteps;
+
+            assert_eq!(
+                metric
+                    .distance(&reference, &Point { x: 6, y: 5 }, &path1, &path2)
+                    .unwrap(),
+                Some(30)
+            );
+            assert_eq!(
+                metric
+                    .distance(&reference, &Point { x: 9, y: 9 }, &path1, &path2)
+                    .unwrap(),
+                None
+            );
+        }
+    }
+
     mod from_comma_separated_str_tests {
         use super::super::{from_comma_separated_str, Segment};
 
@@ -239,17 +1068,259 @@ mod advent3_tests {
             let expected = vec![Segment::R(5), Segment::U(3), Segment::D(8)];
             assert_eq!(segments, expected);
         }
+
+        #[test]
+        fn it_keeps_relative_segments_together() {
+            let segments = from_comma_separated_str("NE2,(3,-6),SW1").unwrap();
+            let expected = vec![Segment::NE(2), Segment::Relative(3, -6), Segment::SW(1)];
+            assert_eq!(segments, expected);
+        }
+    }
+
+    mod wires_from_str_tests {
+        use super::super::{wires_from_str, Segment, WireError, WireReader};
+
+        #[test]
+        fn it_reads_like_the_wire_reader() {
+            for input in &["R5,U3 \n L2, (1,-1)\t\n\n\n", "U1,D2\nL3", "", "\n\nR1\n"] {
+                let expected: Result<Vec<Vec<Segment>>, WireError> =
+                    WireReader::new(input.as_bytes(), "3.txt").collect();
+                assert_eq!(wires_from_str(input, "3.txt").unwrap(), expected.unwrap());
+            }
+        }
+
+        #[test]
+        fn it_reports_where_a_segment_is_invalid() {
+            match wires_from_str("R5,U3\nL2, X7,D1\n", "3.txt") {
+                Err(err @ WireError::Segment { .. }) => assert_eq!(
+                    err.to_string(),
+                    "3.txt:2:5: invalid segment 'X7': UnknownDirectoion(\"X7\")"
+                ),
+                other => panic!("expected a segment error, got {:?}", other),
+            }
+        }
+    }
+
+    mod wire_reader_tests {
+        use super::super::{Segment, SegmentParseError, WireError, WireReader};
+
+        fn read_wires(input: &str) -> Result<Vec<Vec<Segment>>, WireError> {
+            WireReader::new(input.as_bytes(), "3.txt").collect()
+        }
+
+        #[test]
+        fn it_works_as_expected() {
+            let wires = read_wires("R5,U3 \n L2, (1,-1)\t\n\n\n").unwrap();
+            let expected = vec![
+                vec![Segment::R(5), Segment::U(3)],
+                vec![Segment::L(2), Segment::Relative(1, -1)],
+            ];
+            assert_eq!(wires, expected);
+        }
+
+        #[test]
+        fn it_streams_segments() {
+            let mut reader = WireReader::new("U1,D2\nL3".as_bytes(), "3.txt");
+            let mut segments = Vec::new();
+            while reader.read_wire(|s| segments.push(s)).unwrap() {}
+            assert_eq!(segments, vec![Segment::U(1), Segment::D(2), Segment::L(3)]);
+        }
+
+        #[test]
+        fn it_reports_where_a_segment_is_invalid() {
+            match read_wires("R5,U3\nL2, X7,D1\n") {
+                Err(WireError::Segment {
+                    file,
+                    line,
+                    column,
+                    token,
+                    error,
+                }) => {
+                    assert_eq!((file.as_str(), line, column), ("3.txt", 2, 5));
+                    assert_eq!(token, "X7");
+                    assert_eq!(
+                        error,
+                        SegmentParseError::UnknownDirectoion("X7".to_string())
+                    );
+                }
+                other => panic!("expected a segment error, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn it_reports_empty_segments() {
+            match read_wires("R5,,U3") {
+                Err(WireError::Segment { line, column, .. }) => assert_eq!((line, column), (1, 4)),
+                other => panic!("expected a segment error, got {:?}", other),
+            }
+        }
     }
 
     mod points_in_both_paths_tests {
-        use super::super::{points_in_both_paths, Point, Segment};
+        use super::super::{
+            check_lattice_crossings, off_lattice_crossing, points_in_both_paths, segment_spans,
+            Point, Segment, StepKinds, WireError, MAX_PATH_STEPS,
+        };
         use std::collections::HashSet;
 
+        #[test]
+        fn it_finds_diagonal_crossings() {
+            let path1 = vec![Segment::NE(4)];
+            let path2 = vec![Segment::R(4), Segment::NW(4)];
+            let intersection = points_in_both_paths(&path1, &path2).unwrap();
+            let expected: HashSet<Point> = vec![Point { x: 2, y: 2 }].into_iter().collect();
+
+            assert_eq!(intersection, expected);
+        }
+
+        #[test]
+        fn it_rejects_crossings_between_lattice_points() {
+            let path1 = vec![Segment::R(1), Segment::NW(1)];
+            let path2 = vec![Segment::NE(1)];
+            assert!(matches!(
+                points_in_both_paths(&path1, &path2),
+                Err(WireError::OffLatticeCrossing {
+                    segment1: 1,
+                    segment2: 0
+                })
+            ));
+
+            let path2 = vec![Segment::Relative(3, 1), Segment::NE(2)];
+            let path1 = vec![Segment::U(2), Segment::Relative(2, -2)];
+            assert!(matches!(
+                points_in_both_paths(&path1, &path2),
+                Err(WireError::OffLatticeCrossing { .. })
+            ));
+        }
+
+        #[test]
+        fn it_rejects_axis_segments_crossing_steep_ones() {
+            // The second wire crosses y = 0 at x = 2/3.
+            let path1 = vec![Segment::R(3)];
+            let path2 = vec![Segment::D(1), Segment::Relative(2, 3)];
+            assert!(matches!(
+                points_in_both_paths(&path1, &path2),
+                Err(WireError::OffLatticeCrossing {
+                    segment1: 0,
+                    segment2: 1
+                })
+            ));
+        }
+
+        #[test]
+        fn the_sweep_finds_the_crossings_of_all_pairs() {
+            let mut state = 0x2545_f491_4f6c_dd1du64;
+            let mut next = |n: u64| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state % n
+            };
+            let mut segment = || match next(5) {
+                0 => Segment::R(next(4) as usize + 1),
+                1 => Segment::U(next(4) as usize + 1),
+                2 => Segment::NE(next(4) as usize + 1),
+                3 => Segment::SW(next(4) as usize + 1),
+                _ => Segment::Relative(next(9) as i64 - 4, next(9) as i64 - 4),
+            };
+            for _ in 0..500 {
+                let path1: Vec<Segment> = (0..6).map(|_| segment()).collect();
+                let path2: Vec<Segment> = (0..6).map(|_| segment()).collect();
+                if path1
+                    .iter()
+                    .chain(&path2)
+                    .any(|s| *s == Segment::Relative(0, 0))
+                {
+                    continue;
+                }
+                let (spans1, spans2) = (
+                    segment_spans(&path1).unwrap(),
+                    segment_spans(&path2).unwrap(),
+                );
+                let crosses = spans1.iter().any(|a| {
+                    spans2.iter().any(|b| {
+                        off_lattice_crossing((&a.start, &a.step, a.len), (&b.start, &b.step, b.len))
+                            .unwrap()
+                    })
+                });
+                let swept = check_lattice_crossings(&path1, &path2);
+                assert_eq!(
+                    matches!(swept, Err(WireError::OffLatticeCrossing { .. })),
+                    crosses,
+                    "{:?} and {:?}",
+                    path1,
+                    path2
+                );
+            }
+        }
+
+        #[test]
+        fn it_only_sweeps_steps_that_may_cross_between_lattice_points() {
+            let plain = segment_spans(&[Segment::R(2), Segment::U(1), Segment::L(5)]).unwrap();
+            let diagonal = segment_spans(&[Segment::NE(2), Segment::D(1)]).unwrap();
+            let steep = segment_spans(&[Segment::Relative(2, 3)]).unwrap();
+            let (plain, diagonal, steep) = (
+                StepKinds::of(&plain),
+                StepKinds::of(&diagonal),
+                StepKinds::of(&steep),
+            );
+            let (east, north_west) = (Point::new(1, 0), Point::new(-1, 1));
+
+            assert!(!plain.may_cross(&east));
+            assert!(!plain.may_cross(&north_west));
+            assert!(!diagonal.may_cross(&east));
+            assert!(diagonal.may_cross(&north_west));
+            assert!(steep.may_cross(&east));
+            assert!(plain.may_cross(&Point::new(2, 3)));
+        }
+
+        #[test]
+        fn it_names_the_wire_that_overflows() {
+            let path1 = vec![Segment::U(1)];
+            let path2 = vec![Segment::R(i64::MAX as usize), Segment::R(1)];
+            let err = points_in_both_paths(&path1, &path2).unwrap_err();
+            assert!(matches!(
+                err,
+                WireError::Overflow {
+                    wire: Some(1),
+                    segment_index: 1,
+                    ..
+                }
+            ));
+            assert_eq!(
+                err.to_string(),
+                "segment 2 (R(1)) of the second wire leaves the coordinate range"
+            );
+
+            let path1 = vec![Segment::L(MAX_PATH_STEPS), Segment::L(1)];
+            assert!(matches!(
+                points_in_both_paths(&path1, &[Segment::U(1)]),
+                Err(WireError::TooLong { wire: Some(0), .. })
+            ));
+        }
+
+        #[test]
+        fn it_keeps_crossings_at_lattice_points() {
+            // Diagonals that cross at a lattice point, and a diagonal that
+            // only touches the end of another one.
+            let path1 = vec![Segment::NE(2)];
+            let path2 = vec![Segment::R(2), Segment::NW(2)];
+            let expected: HashSet<Point> = vec![Point::new(1, 1)].into_iter().collect();
+            assert_eq!(points_in_both_paths(&path1, &path2).unwrap(), expected);
+
+            let path2 = vec![Segment::R(3), Segment::NW(1)];
+            let expected: HashSet<Point> = vec![Point::new(1, 0), Point::new(2, 1)]
+                .into_iter()
+                .collect();
+            let path1 = vec![Segment::R(1), Segment::NE(1)];
+            assert_eq!(points_in_both_paths(&path1, &path2).unwrap(), expected);
+        }
+
         #[test]
         fn it_works_as_expected() {
             let path1 = vec![Segment::R(8), Segment::U(5), Segment::L(5), Segment::D(3)];
             let path2 = vec![Segment::U(7), Segment::R(6), Segment::D(4), Segment::L(4)];
-            let intersection = points_in_both_paths(&path1, &path2);
+            let intersection = points_in_both_paths(&path1, &path2).unwrap();
             let expected: HashSet<Point> = {
                 let mut s = HashSet::new();
                 s.insert(Point { x: 3, y: 3 });
@@ -268,7 +1339,7 @@ mod advent3_tests {
 
             #[test]
             fn it_complains_when_segments_are_too_short() {
-                for s in vec!["", "X"] {
+                for s in ["", "X"] {
                     let s = String::from(s);
                     assert_eq!(
                         s.parse::<Segment>().unwrap_err(),
@@ -279,7 +1350,7 @@ mod advent3_tests {
 
             #[test]
             fn it_complains_when_direction_is_unknown() {
-                for s in vec!["X10", "r10"] {
+                for s in ["X10", "r10"] {
                     let s = String::from(s);
                     assert_eq!(
                         s.parse::<Segment>().unwrap_err(),
@@ -290,7 +1361,7 @@ mod advent3_tests {
 
             #[test]
             fn it_complains_when_length_is_not_a_number() -> Result<(), String> {
-                for s in vec!["DD", "Rxxx"] {
+                for s in ["DD", "Rxxx"] {
                     let s = String::from(s);
                     match s.parse::<Segment>().unwrap_err() {
                         SegmentParseError::LengthParseError(_) => {}
@@ -302,7 +1373,7 @@ mod advent3_tests {
 
             #[test]
             fn it_complains_when_length_is_non_positive() {
-                for s in vec!["R0", "D0"] {
+                for s in ["R0", "D0"] {
                     let s = String::from(s);
                     assert_eq!(
                         s.parse::<Segment>().unwrap_err(),
@@ -313,36 +1384,58 @@ mod advent3_tests {
 
             #[test]
             fn it_works_as_expected() {
-                for (s, expected) in vec![
+                for (s, expected) in [
                     ("R10", Segment::R(10)),
                     ("U9", Segment::U(9)),
                     ("L1", Segment::L(1)),
+                    ("NE3", Segment::NE(3)),
+                    ("SW12", Segment::SW(12)),
+                    ("(2,-4)", Segment::Relative(2, -4)),
+                    ("( -1, 0)", Segment::Relative(-1, 0)),
                 ] {
                     let s = String::from(s);
                     assert_eq!(s.parse::<Segment>().unwrap(), expected);
                 }
             }
+
+            #[test]
+            fn it_complains_about_malformed_relative_segments() {
+                for s in ["(1,2", "(1)", "(1,2,3)"] {
+                    let s = String::from(s);
+                    assert_eq!(
+                        s.parse::<Segment>().unwrap_err(),
+                        SegmentParseError::MalformedRelative(s)
+                    );
+                }
+                assert_eq!(
+                    "(0,0)".parse::<Segment>().unwrap_err(),
+                    SegmentParseError::LengthZero("(0,0)".to_string())
+                );
+            }
         }
     }
 
-    mod point_tests {
-        use super::super::{Point, Segment};
+    mod wire_walker_tests {
+        use super::super::{Point, Segment, WireWalker};
         use std::collections::HashSet;
         use std::iter::FromIterator;
 
-        #[test]
-        fn the_norm_works_as_expected() {
-            let p = Point { x: -3, y: 2 };
-            assert_eq!(p.manhattan_norm(), 5);
-        }
-
         #[test]
         fn points_in_seqment_works_as_expected() {
-            let test_cases: Vec<((i64, i64), _, Vec<(i64, i64)>)> = vec![
+            type Coordinates = (i64, i64);
+            let test_cases: Vec<(Coordinates, _, Vec<Coordinates>)> = vec![
                 ((3, 2), Segment::R(4), vec![(4, 2), (5, 2), (6, 2), (7, 2)]),
                 ((1, -1), Segment::U(2), vec![(1, 0), (1, 1)]),
                 ((0, 0), Segment::L(3), vec![(-1, 0), (-2, 0), (-3, 0)]),
                 ((4, 4), Segment::D(4), vec![(4, 3), (4, 2), (4, 1), (4, 0)]),
+                ((0, 0), Segment::NE(2), vec![(1, 1), (2, 2)]),
+                ((0, 0), Segment::SW(1), vec![(-1, -1)]),
+                ((1, 1), Segment::Relative(4, -6), vec![(3, -2), (5, -5)]),
+                (
+                    (0, 0),
+                    Segment::Relative(0, 3),
+                    vec![(0, 1), (0, 2), (0, 3)],
+                ),
             ];
 
             for (origin, segment, expected) in test_cases {
@@ -352,16 +1445,20 @@ mod advent3_tests {
                 };
                 let expected: Vec<Point> =
                     expected.iter().map(|p| Point { x: p.0, y: p.1 }).collect();
-                let points_in_segment = origin.points_in_segment(segment);
+                let points_in_segment = origin
+                    .points_in_segment(segment)
+                    .map(|points| points.collect::<Vec<_>>());
 
-                assert_eq!(points_in_segment, expected);
+                assert_eq!(points_in_segment, Some(expected));
             }
         }
 
+        #[rustfmt::skip]
         static TEST_PATH: [Segment; 5] = [
             Segment::R(4), Segment::U(4), Segment::L(4), Segment::D(2), Segment::R(6),
         ];
 
+        #[rustfmt::skip]
         static POINTS_IN_TEST_PATH: [(i64, i64); 20] = [
             (1, 0), (2, 0), (3, 0), (4, 0),
             (4, 1), (4, 2), (4, 3), (4, 4),
@@ -372,9 +1469,7 @@ mod advent3_tests {
 
         #[test]
         fn points_in_path_works_as_expected() {
-            let calculated_path_points = Point { x: 0, y: 0 }.points_in_path(
-                &TEST_PATH.to_vec()
-            );
+            let calculated_path_points = Point { x: 0, y: 0 }.points_in_path(&TEST_PATH).unwrap();
             let points: Vec<_> = POINTS_IN_TEST_PATH
                 .to_vec()
                 .iter()
@@ -385,18 +1480,245 @@ mod advent3_tests {
 
         #[test]
         fn point_set_in_path_works_as_expected() {
-            let calculated_path_points = Point { x: 0, y: 0 }.point_set_in_path(
-                &TEST_PATH.to_vec()
-            );
+            let calculated_path_points =
+                Point { x: 0, y: 0 }.point_set_in_path(&TEST_PATH).unwrap();
             let expected_points = {
                 let points: Vec<_> = POINTS_IN_TEST_PATH
                     .to_vec()
                     .iter()
                     .map(|(x, y)| Point { x: *x, y: *y })
                     .collect();
-                    HashSet::from_iter(points)
+                HashSet::from_iter(points)
             };
             assert_eq!(expected_points, calculated_path_points);
         }
     }
+    mod overflow_tests {
+        use super::super::{Metric, Point, Segment, WireError, WireWalker, MAX_PATH_STEPS};
+
+        /// A xorshift generator so the property tests below are reproducible
+        /// without pulling in a dependency.
+        struct Rng(u64);
+
+        impl Rng {
+            fn next(&mut self) -> u64 {
+                self.0 ^= self.0 << 13;
+                self.0 ^= self.0 >> 7;
+                self.0 ^= self.0 << 17;
+                self.0
+            }
+
+            /// A non-zero value close to one of the edges of the integer
+            /// ranges involved.
+            fn extreme_u64(&mut self) -> u64 {
+                let edges = [
+                    1,
+                    1 << 32,
+                    i64::MAX as u64 / 2,
+                    i64::MAX as u64,
+                    1 << 63,
+                    u64::MAX,
+                ];
+                let edge = edges[(self.next() % edges.len() as u64) as usize];
+                let jitter = self.next() % 5;
+                if self.next() & 1 == 0 {
+                    edge.saturating_add(jitter)
+                } else {
+                    edge.saturating_sub(jitter).max(1)
+                }
+            }
+
+            fn extreme_i64(&mut self) -> i64 {
+                let value = self.extreme_u64().min(i64::MAX as u64) as i64;
+                if self.next() & 1 == 0 {
+                    value
+                } else {
+                    -value - (self.next() % 2) as i64
+                }
+            }
+
+            fn segment(&mut self, length: usize) -> Segment {
+                match self.next() % 9 {
+                    0 => Segment::R(length),
+                    1 => Segment::L(length),
+                    2 => Segment::U(length),
+                    3 => Segment::D(length),
+                    4 => Segment::NE(length),
+                    5 => Segment::NW(length),
+                    6 => Segment::SE(length),
+                    7 => Segment::SW(length),
+                    _ => Segment::Relative(self.extreme_i64(), self.extreme_i64()),
+                }
+            }
+        }
+
+        fn segment_to_string(segment: Segment) -> String {
+            let (direction, length) = match segment {
+                Segment::R(n) => ("R", n),
+                Segment::L(n) => ("L", n),
+                Segment::U(n) => ("U", n),
+                Segment::D(n) => ("D", n),
+                Segment::NE(n) => ("NE", n),
+                Segment::NW(n) => ("NW", n),
+                Segment::SE(n) => ("SE", n),
+                Segment::SW(n) => ("SW", n),
+                Segment::Relative(dx, dy) => return format!("({},{})", dx, dy),
+            };
+            format!("{}{}", direction, length)
+        }
+
+        /// Where the segment ends when computed without any chance of
+        /// overflow.
+        fn exact_end(start: &Point, segment: Segment) -> (i128, i128) {
+            let (dx, dy) = match (segment.direction(), segment) {
+                (_, Segment::Relative(dx, dy)) => (dx as i128, dy as i128),
+                (Some(direction), _) => {
+                    let delta = direction.delta();
+                    let length = segment.len() as i128;
+                    (delta.x as i128 * length, delta.y as i128 * length)
+                }
+                (None, _) => unreachable!(),
+            };
+            (start.x as i128 + dx, start.y as i128 + dy)
+        }
+
+        fn fits(coordinate: i128) -> bool {
+            coordinate >= i64::MIN as i128 && coordinate <= i64::MAX as i128
+        }
+
+        #[test]
+        fn the_parser_keeps_extreme_segments_intact() {
+            let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+            for _ in 0..1000 {
+                let length = rng.extreme_u64() as usize;
+                let segment = rng.segment(length);
+                if segment == Segment::Relative(0, 0) {
+                    continue;
+                }
+                let parsed = segment_to_string(segment).parse::<Segment>();
+                assert_eq!(parsed, Ok(segment));
+            }
+        }
+
+        #[test]
+        fn the_parser_rejects_lengths_beyond_usize() {
+            let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+            for _ in 0..100 {
+                let length = usize::MAX as u128 + 1 + (rng.next() % 1000) as u128;
+                let token = format!("R{}", length);
+                match token.parse::<Segment>() {
+                    Err(super::super::SegmentParseError::LengthParseError(_)) => {}
+                    other => panic!("expected a length error for {}, got {:?}", token, other),
+                }
+            }
+        }
+
+        #[test]
+        fn the_walker_reports_overflowing_segments() {
+            let mut rng = Rng(0xdead_beef_cafe_f00d);
+            let mut overflows = 0;
+            for _ in 0..1000 {
+                let start = Point::new(rng.extreme_i64(), rng.extreme_i64());
+                let length = rng.extreme_u64() as usize;
+                let segment = rng.segment(length);
+                if segment == Segment::Relative(0, 0) {
+                    continue;
+                }
+                let (x, y) = exact_end(&start, segment);
+                if fits(x) && fits(y) {
+                    let end = segment.checked_point_after(&start, segment.len());
+                    assert_eq!(end, Some(Point::new(x as i64, y as i64)));
+                    continue;
+                }
+
+                overflows += 1;
+                // Reach the start through another segment, so the reported
+                // index is checked as well.
+                let (lead_in, start) = if start.x > 0 {
+                    (Segment::R(1), start - Point::new(1, 0))
+                } else {
+                    (Segment::L(1), start + Point::new(1, 0))
+                };
+                match start.points_in_path(&[lead_in, segment]) {
+                    Err(WireError::Overflow {
+                        wire: None,
+                        segment_index: 1,
+                        segment: s,
+                    }) => assert_eq!(s, segment),
+                    other => panic!(
+                        "expected {:?} from {:?} to overflow, got {:?}",
+                        segment, start, other
+                    ),
+                }
+            }
+            assert!(overflows > 100);
+        }
+
+        #[test]
+        fn short_segments_near_the_edges_never_wrap() {
+            let mut rng = Rng(0x0123_4567_89ab_cdef);
+            for _ in 0..1000 {
+                let start = Point::new(rng.extreme_i64(), rng.extreme_i64());
+                let length = 1 + (rng.next() % 3) as usize;
+                let segment = rng.segment(length);
+                if let Segment::Relative(_, _) = segment {
+                    continue;
+                }
+                let (x, y) = exact_end(&start, segment);
+                let points = start
+                    .points_in_segment(segment)
+                    .map(|points| points.collect::<Vec<_>>());
+                if fits(x) && fits(y) {
+                    let points = points.unwrap();
+                    assert_eq!(points.len(), segment.len());
+                    assert_eq!(points.last(), Some(&Point::new(x as i64, y as i64)));
+                } else {
+                    assert_eq!(points, None);
+                }
+            }
+        }
+
+        #[test]
+        fn long_wires_fail_instead_of_exhausting_memory() {
+            let start = Point::origin();
+            let segment = Segment::R(2_000_000_000);
+            assert_eq!(
+                start.points_in_segment(segment).unwrap().nth(2),
+                Some(Point::new(3, 0))
+            );
+            match start.points_in_path(&[Segment::U(1), segment]) {
+                Err(WireError::TooLong {
+                    wire: None,
+                    segment_index: 1,
+                    segment: s,
+                }) => assert_eq!(s, segment),
+                other => panic!("expected a wire that is too long, got {:?}", other),
+            }
+
+            let half = Segment::L(MAX_PATH_STEPS / 2);
+            assert_eq!(
+                start.points_in_path(&[half, half]).unwrap().len(),
+                MAX_PATH_STEPS
+            );
+            assert!(matches!(
+                start.points_in_path(&[half, half, Segment::D(1)]),
+                Err(WireError::TooLong {
+                    segment_index: 2,
+                    ..
+                })
+            ));
+        }
+
+        #[test]
+        fn distances_report_overflow() {
+            let reference = Point::new(i64::MIN, i64::MIN);
+            let point = Point::new(i64::MAX, 0);
+            for metric in [Metric::Manhattan, Metric::EuclideanSquared] {
+                match metric.distance(&reference, &point, &[], &[]) {
+                    Err(WireError::DistanceOverflow(p)) => assert_eq!(p, point),
+                    other => panic!("expected an overflow, got {:?}", other),
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,223 @@
+//! Recorded answers of the riddles, one `day.part = answer` per line. Blank
+//! lines and lines starting with `#` are kept as they are when the answers
+//! are written back.
+
+use std::fmt;
+use std::fs;
+
+use super::RiddleError;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+enum Line {
+    Answer {
+        key: String,
+        value: String,
+    },
+    /// A comment or a blank line.
+    Other(String),
+}
+
+/// The lines of an answers file.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Answers {
+    lines: Vec<Line>,
+}
+
+impl Answers {
+    /// Parses answers. `file` names the answers in errors.
+    pub fn parse(text: &str, file: &str) -> Result<Answers, RiddleError> {
+        let mut answers = Answers::default();
+        for (i, line) in text.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                answers.lines.push(Line::Other(line.to_string()));
+                continue;
+            }
+            let (key, value) = trimmed
+                .split_once('=')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .filter(|(key, value)| is_key(key) && !value.is_empty())
+                .ok_or_else(|| {
+                    RiddleError::Generic(format!(
+                        "{}:{}: expected 'day.part = answer', got '{}'",
+                        file,
+                        i + 1,
+                        line
+                    ))
+                })?;
+            if answers.get(key).is_some() {
+                return Err(RiddleError::Generic(format!(
+                    "{}:{}: {} is recorded more than once",
+                    file,
+                    i + 1,
+                    key
+                )));
+            }
+            answers.lines.push(Line::Answer {
+                key: key.to_string(),
+                value: value.to_string(),
+            });
+        }
+        Ok(answers)
+    }
+
+    pub fn from_file(path: &str) -> Result<Answers, RiddleError> {
+        let text = fs::read_to_string(path).map_err(|err| {
+            RiddleError::IO(std::io::Error::new(
+                err.kind(),
+                format!("cannot read the answers in {}: {}", path, err),
+            ))
+        })?;
+        Answers::parse(&text, path)
+    }
+
+    /// Writes the answers to `path`, creating its directory if needed.
+    pub fn write_to_file(&self, path: &str) -> Result<(), RiddleError> {
+        if let Some(dir) = std::path::Path::new(path).parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    /// The recorded answer of a riddle like `1.2`.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.lines.iter().find_map(|line| match line {
+            Line::Answer { key: k, value } if k == key => Some(value.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Records an answer, replacing the previous one in place or adding it
+    /// at the end.
+    pub fn set(&mut self, key: &str, value: &str) {
+        for line in &mut self.lines {
+            if let Line::Answer { key: k, value: v } = line {
+                if k == key {
+                    *v = value.to_string();
+                    return;
+                }
+            }
+        }
+        self.lines.push(Line::Answer {
+            key: key.to_string(),
+            value: value.to_string(),
+        });
+    }
+}
+
+fn is_key(key: &str) -> bool {
+    match key.split_once('.') {
+        Some((day, part)) => day.parse::<u8>().is_ok() && part.parse::<u8>().is_ok(),
+        None => false,
+    }
+}
+
+impl fmt::Display for Answers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in &self.lines {
+            match line {
+                Line::Answer { key, value } => writeln!(f, "{} = {}", key, value)?,
+                Line::Other(text) => writeln!(f, "{}", text)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A line diff of an expected and an actual answer. Lines that differ are
+/// prefixed with `-` for the expected and `+` for the actual answer, equal
+/// lines with two spaces.
+pub fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let mut diff = String::new();
+    for i in 0..expected.len().max(actual.len()) {
+        match (expected.get(i), actual.get(i)) {
+            (Some(e), Some(a)) if e == a => diff.push_str(&format!("  {}\n", e)),
+            (e, a) => {
+                if let Some(e) = e {
+                    diff.push_str(&format!("- {}\n", e));
+                }
+                if let Some(a) = a {
+                    diff.push_str(&format!("+ {}\n", a));
+                }
+            }
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod answers_tests {
+    mod parse_tests {
+        use super::super::super::config::Config;
+        use super::super::super::RiddleError;
+        use super::super::Answers;
+
+        #[test]
+        fn it_reads_answers() {
+            let answers = Answers::parse("# day 1\n1.1 = 3210097\n\n 2.2=8051 \n", "a").unwrap();
+            assert_eq!(answers.get("1.1"), Some("3210097"));
+            assert_eq!(answers.get("2.2"), Some("8051"));
+            assert_eq!(answers.get("1.2"), None);
+        }
+
+        #[test]
+        fn it_names_the_line_of_errors() {
+            for (text, message) in &[
+                (
+                    "1.1 = 1\n1.2\n",
+                    "a:2: expected 'day.part = answer', got '1.2'",
+                ),
+                (
+                    "one.1 = 1\n",
+                    "a:1: expected 'day.part = answer', got 'one.1 = 1'",
+                ),
+                ("1.1 =\n", "a:1: expected 'day.part = answer', got '1.1 ='"),
+                ("1.1 = 1\n1.1 = 2\n", "a:2: 1.1 is recorded more than once"),
+            ] {
+                match Answers::parse(text, "a") {
+                    Err(RiddleError::Generic(err)) => assert_eq!(&err, message),
+                    other => panic!("expected an error, got {:?}", other),
+                }
+            }
+        }
+
+        #[test]
+        fn it_reads_the_default_answers() {
+            let answers = Answers::from_file(&Config::default().answers_path()).unwrap();
+            assert_eq!(answers.get("3.2"), Some("164012"));
+        }
+
+        #[test]
+        fn it_reports_missing_files() {
+            assert!(Answers::from_file("./does/not/exist").is_err());
+        }
+    }
+
+    mod set_tests {
+        use super::super::Answers;
+
+        #[test]
+        fn it_keeps_the_other_lines() {
+            let mut answers = Answers::parse("# answers\n1.1 = 1\n\n1.2 = 2\n", "a").unwrap();
+            answers.set("1.1", "3");
+            answers.set("2.1", "4");
+            assert_eq!(
+                answers.to_string(),
+                "# answers\n1.1 = 3\n\n1.2 = 2\n2.1 = 4\n"
+            );
+        }
+    }
+
+    mod diff_tests {
+        use super::super::diff;
+
+        #[test]
+        fn it_marks_the_lines_that_differ() {
+            assert_eq!(diff("42", "4981"), "- 42\n+ 4981\n");
+            assert_eq!(diff("#.\n.#\n", "#.\n##\n#."), "  #.\n- .#\n+ ##\n+ #.\n");
+        }
+    }
+}
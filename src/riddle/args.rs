@@ -0,0 +1,249 @@
+//! The options riddles accept. Each riddle declares its options in an
+//! `ArgSchema`, which parses and validates the `key=value` arguments before
+//! the riddle reads them as typed values.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use super::RiddleError;
+
+/// The kind of value an option takes.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ArgKind {
+    /// `true` or `false`.
+    Bool,
+    Integer,
+    /// An integer greater than zero.
+    PositiveInteger,
+    /// One of the given values.
+    Choice(&'static [&'static str]),
+    /// Any value, checked by the riddle. The text is shown in the usage,
+    /// like `<x>,<y>`.
+    Text(&'static str),
+}
+
+impl ArgKind {
+    fn accepts(&self, value: &str) -> bool {
+        match self {
+            ArgKind::Bool => value == "true" || value == "false",
+            ArgKind::Integer => value.parse::<i64>().is_ok(),
+            ArgKind::PositiveInteger => value.parse::<i64>().is_ok_and(|n| n > 0),
+            ArgKind::Choice(choices) => choices.contains(&value),
+            ArgKind::Text(_) => true,
+        }
+    }
+}
+
+impl fmt::Display for ArgKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArgKind::Bool => write!(f, "true|false"),
+            ArgKind::Integer | ArgKind::PositiveInteger => write!(f, "<n>"),
+            ArgKind::Choice(choices) => write!(f, "{}", choices.join("|")),
+            ArgKind::Text(placeholder) => write!(f, "{}", placeholder),
+        }
+    }
+}
+
+/// An option of a riddle.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ArgSpec {
+    pub name: &'static str,
+    pub kind: ArgKind,
+    pub help: &'static str,
+}
+
+/// The options of a riddle.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ArgSchema {
+    pub options: &'static [ArgSpec],
+}
+
+/// The schema of riddles without options.
+pub const NO_ARGS: ArgSchema = ArgSchema { options: &[] };
+
+impl ArgSchema {
+    /// Parses `key=value` arguments. Fails with `RiddleError::ArgumentsCount`
+    /// for arguments that are not of that form, since riddles take no
+    /// positional arguments, and with `RiddleError::InvalidArguments` for
+    /// unknown, repeated or invalid options.
+    pub fn parse(&'static self, args: &[String]) -> Result<Args, RiddleError> {
+        let positional = args.iter().filter(|arg| !arg.contains('=')).count();
+        if positional > 0 {
+            return Err(RiddleError::ArgumentsCount(
+                0,
+                positional.min(u8::MAX as usize) as u8,
+            ));
+        }
+
+        let mut values = HashMap::new();
+        for arg in args {
+            let (key, value) = arg.split_once('=').unwrap_or((arg, ""));
+            let spec = match self.options.iter().find(|spec| spec.name == key) {
+                Some(spec) => spec,
+                None => return Err(self.invalid(format!("unknown option '{}'", key))),
+            };
+            if !spec.kind.accepts(value) {
+                return Err(self.invalid(format!("{} expects {}, got '{}'", key, spec.kind, value)));
+            }
+            if values.insert(spec.name, value.to_string()).is_some() {
+                return Err(self.invalid(format!("{} is given more than once", key)));
+            }
+        }
+        Ok(Args {
+            schema: self,
+            values,
+        })
+    }
+
+    /// Lists the options, one per line.
+    pub fn usage(&self) -> String {
+        if self.options.is_empty() {
+            return "  (no options)\n".to_string();
+        }
+        let options: Vec<(String, &str)> = self
+            .options
+            .iter()
+            .map(|spec| (format!("{}={}", spec.name, spec.kind), spec.help))
+            .collect();
+        let width = options.iter().map(|(option, _)| option.len()).max();
+        options
+            .iter()
+            .map(|(option, help)| {
+                format!(
+                    "  {:<width$}  {}\n",
+                    option,
+                    help,
+                    width = width.unwrap_or(0)
+                )
+            })
+            .collect()
+    }
+
+    fn invalid(&self, message: String) -> RiddleError {
+        RiddleError::InvalidArguments {
+            message,
+            usage: self.usage(),
+        }
+    }
+}
+
+/// The validated options of a riddle.
+#[derive(Debug)]
+pub struct Args {
+    schema: &'static ArgSchema,
+    values: HashMap<&'static str, String>,
+}
+
+impl Args {
+    /// The value of an option, or `None` if it is not given.
+    pub fn value<T: FromStr>(&self, name: &str) -> Result<Option<T>, RiddleError>
+    where
+        T::Err: fmt::Debug,
+    {
+        match self.values.get(name) {
+            Some(value) => value.parse().map(Some).map_err(|err| {
+                self.schema
+                    .invalid(format!("invalid {} '{}': {:?}", name, value, err))
+            }),
+            None => Ok(None),
+        }
+    }
+
+    /// The value of a boolean option, or `false` if it is not given.
+    pub fn flag(&self, name: &str) -> bool {
+        self.values.get(name).is_some_and(|value| value == "true")
+    }
+}
+
+#[cfg(test)]
+mod args_tests {
+    use super::{ArgKind, ArgSchema, ArgSpec};
+
+    const SCHEMA: ArgSchema = ArgSchema {
+        options: &[
+            ArgSpec {
+                name: "lenient",
+                kind: ArgKind::Bool,
+                help: "skips invalid lines",
+            },
+            ArgSpec {
+                name: "threads",
+                kind: ArgKind::PositiveInteger,
+                help: "the number of threads",
+            },
+            ArgSpec {
+                name: "metric",
+                kind: ArgKind::Choice(&["manhattan", "chebyshev"]),
+                help: "the distance metric",
+            },
+        ],
+    };
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    mod parse_tests {
+        use super::super::super::RiddleError;
+        use super::super::NO_ARGS;
+        use super::{strings, SCHEMA};
+
+        #[test]
+        fn it_reads_typed_values() {
+            let args = SCHEMA
+                .parse(&strings(&["threads=4", "lenient=true"]))
+                .unwrap();
+            assert_eq!(args.value::<usize>("threads").unwrap(), Some(4));
+            assert_eq!(args.value::<String>("metric").unwrap(), None);
+            assert!(args.flag("lenient"));
+        }
+
+        #[test]
+        fn it_rejects_invalid_options() {
+            for arg in &[
+                "colour=red",
+                "threads=0",
+                "threads=x",
+                "lenient=yes",
+                "metric=taxicab",
+            ] {
+                match SCHEMA.parse(&strings(&[arg])) {
+                    Err(RiddleError::InvalidArguments { usage, .. }) => {
+                        assert_eq!(usage, SCHEMA.usage())
+                    }
+                    other => panic!("expected {} to be rejected, got {:?}", arg, other),
+                }
+            }
+            assert!(SCHEMA.parse(&strings(&["threads=2", "threads=3"])).is_err());
+            assert!(NO_ARGS.parse(&strings(&["threads=2"])).is_err());
+        }
+
+        #[test]
+        fn it_counts_positional_arguments() {
+            match SCHEMA.parse(&strings(&["lenient", "threads=2", "3.1"])) {
+                Err(RiddleError::ArgumentsCount(expected, given)) => {
+                    assert_eq!((expected, given), (0, 2))
+                }
+                other => panic!("expected an arguments count error, got {:?}", other),
+            }
+        }
+    }
+
+    mod usage_tests {
+        use super::super::NO_ARGS;
+        use super::SCHEMA;
+
+        #[test]
+        fn it_lists_the_options() {
+            let expected = [
+                "  lenient=true|false          skips invalid lines",
+                "  threads=<n>                 the number of threads",
+                "  metric=manhattan|chebyshev  the distance metric",
+            ];
+            assert_eq!(SCHEMA.usage(), expected.join("\n") + "\n");
+            assert_eq!(NO_ARGS.usage(), "  (no options)\n");
+        }
+    }
+}
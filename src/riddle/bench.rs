@@ -0,0 +1,314 @@
+//! Benchmarks of riddles. A riddle is solved a number of times after a
+//! warm-up, and the times of parsing, solving and both together are
+//! summarized. Results can be saved as JSON and compared with a baseline.
+
+use std::convert::TryFrom;
+use std::fs;
+use std::time::Duration;
+
+use super::super::json::Json;
+use super::{nanos, Riddle, RiddleError};
+
+/// A summary of the times of several runs.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+    /// Summarizes the times. Returns `None` if there are none.
+    pub fn of(times: &[Duration]) -> Option<Stats> {
+        let mut sorted = times.to_vec();
+        sorted.sort_unstable();
+        let min = *sorted.first()?;
+        let middle = sorted.len() / 2;
+        let median = if sorted.len() & 1 == 0 {
+            (sorted[middle - 1] + sorted[middle]) / 2
+        } else {
+            sorted[middle]
+        };
+        let mean = sorted.iter().sum::<Duration>() / sorted.len() as u32;
+        let variance = sorted
+            .iter()
+            .map(|time| (time.as_secs_f64() - mean.as_secs_f64()).powi(2))
+            .sum::<f64>()
+            / sorted.len() as f64;
+        Some(Stats {
+            min,
+            median,
+            mean,
+            stddev: Duration::from_secs_f64(variance.sqrt()),
+        })
+    }
+
+    pub fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("min_ns", nanos(self.min)),
+            ("median_ns", nanos(self.median)),
+            ("mean_ns", nanos(self.mean)),
+            ("stddev_ns", nanos(self.stddev)),
+        ])
+    }
+
+    pub fn from_json(json: &Json) -> Option<Stats> {
+        let duration = |key| {
+            let nanos = json.get(key)?.as_i64()?;
+            u64::try_from(nanos).ok().map(Duration::from_nanos)
+        };
+        Some(Stats {
+            min: duration("min_ns")?,
+            median: duration("median_ns")?,
+            mean: duration("mean_ns")?,
+            stddev: duration("stddev_ns")?,
+        })
+    }
+}
+
+/// The benchmark of a riddle.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BenchResult {
+    /// The key of the riddle, like `1.2`.
+    pub riddle: String,
+    pub runs: usize,
+    pub total: Stats,
+    /// Parsing the input, for riddles that do that in a step of its own.
+    pub parse: Option<Stats>,
+    pub solve: Stats,
+}
+
+impl BenchResult {
+    pub fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("riddle", Json::string(self.riddle.as_str())),
+            ("runs", Json::Int(self.runs as i64)),
+            ("total", self.total.to_json()),
+            (
+                "parse",
+                self.parse.map_or(Json::Null, |parse| parse.to_json()),
+            ),
+            ("solve", self.solve.to_json()),
+        ])
+    }
+
+    pub fn from_json(json: &Json) -> Option<BenchResult> {
+        let parse = match json.get("parse")? {
+            Json::Null => None,
+            parse => Some(Stats::from_json(parse)?),
+        };
+        Some(BenchResult {
+            riddle: json.get("riddle")?.as_str()?.to_string(),
+            runs: usize::try_from(json.get("runs")?.as_i64()?).ok()?,
+            total: Stats::from_json(json.get("total")?)?,
+            parse,
+            solve: Stats::from_json(json.get("solve")?)?,
+        })
+    }
+}
+
+/// Solves a riddle `warmup` times without measuring it, and then `runs`
+/// times. Fails with the first error of the riddle.
+pub fn bench(
+    key: &str,
+    riddle: &dyn Riddle,
+    args: &[String],
+    warmup: usize,
+    runs: usize,
+) -> Result<BenchResult, RiddleError> {
+    for _ in 0..warmup {
+        riddle.solve_timed_anew(args)?;
+    }
+    let mut timings = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        timings.push(riddle.solve_timed_anew(args)?.1);
+    }
+    let times = |time: &dyn Fn(&super::Timings) -> Duration| {
+        timings.iter().map(time).collect::<Vec<Duration>>()
+    };
+    let no_runs = || RiddleError::Generic("a benchmark needs at least one run".to_string());
+    let parse: Option<Vec<Duration>> = timings.iter().map(|timings| timings.parse).collect();
+    Ok(BenchResult {
+        riddle: key.to_string(),
+        runs,
+        total: Stats::of(&times(&|timings| timings.total())).ok_or_else(no_runs)?,
+        parse: parse.as_deref().and_then(Stats::of),
+        solve: Stats::of(&times(&|timings| timings.solve)).ok_or_else(no_runs)?,
+    })
+}
+
+/// Writes benchmark results as JSON, as read by `read_results`.
+pub fn write_results(path: &str, results: &[BenchResult]) -> Result<(), RiddleError> {
+    let results = results.iter().map(BenchResult::to_json).collect();
+    let json = Json::object(vec![("riddles", Json::Array(results))]);
+    fs::write(path, format!("{}\n", json))?;
+    Ok(())
+}
+
+pub fn read_results(path: &str) -> Result<Vec<BenchResult>, RiddleError> {
+    let text = fs::read_to_string(path)?;
+    let json =
+        Json::parse(&text).map_err(|err| RiddleError::Generic(format!("{}: {}", path, err)))?;
+    json.get("riddles")
+        .and_then(Json::as_array)
+        .and_then(|riddles| riddles.iter().map(BenchResult::from_json).collect())
+        .ok_or_else(|| {
+            RiddleError::Generic(format!("{}: expected benchmark results of riddles", path))
+        })
+}
+
+/// A riddle whose median time got slower than in the baseline.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Regression {
+    pub riddle: String,
+    pub baseline: Duration,
+    pub current: Duration,
+}
+
+impl Regression {
+    /// How much slower the riddle got, in percent.
+    pub fn slowdown(&self) -> f64 {
+        (self.current.as_secs_f64() / self.baseline.as_secs_f64() - 1.0) * 100.0
+    }
+}
+
+/// The riddles whose median total time is more than `threshold` percent
+/// slower than in the baseline. Riddles missing from the baseline are not
+/// compared.
+pub fn regressions(
+    results: &[BenchResult],
+    baseline: &[BenchResult],
+    threshold: f64,
+) -> Vec<Regression> {
+    results
+        .iter()
+        .filter_map(|result| {
+            let base = baseline.iter().find(|base| base.riddle == result.riddle)?;
+            let regression = Regression {
+                riddle: result.riddle.clone(),
+                baseline: base.total.median,
+                current: result.total.median,
+            };
+            Some(regression).filter(|regression| regression.slowdown() > threshold)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod bench_tests {
+    use std::time::Duration;
+
+    fn millis(times: &[u64]) -> Vec<Duration> {
+        times.iter().map(|&ms| Duration::from_millis(ms)).collect()
+    }
+
+    mod stats_tests {
+        use super::super::super::super::json::Json;
+        use super::super::Stats;
+        use super::millis;
+        use std::time::Duration;
+
+        #[test]
+        fn it_summarizes_times() {
+            let stats = Stats::of(&millis(&[4, 1, 3, 2])).unwrap();
+            assert_eq!(stats.min, Duration::from_millis(1));
+            assert_eq!(stats.median, Duration::from_micros(2500));
+            assert_eq!(stats.mean, Duration::from_micros(2500));
+            assert_eq!(stats.stddev.as_micros(), 1118);
+
+            let stats = Stats::of(&millis(&[5, 1, 3])).unwrap();
+            assert_eq!(stats.median, Duration::from_millis(3));
+            assert_eq!(Stats::of(&[]), None);
+        }
+
+        #[test]
+        fn it_converts_to_json_and_back() {
+            let stats = Stats::of(&millis(&[1, 2, 6])).unwrap();
+            let json = Json::parse(&stats.to_json().to_string()).unwrap();
+            assert_eq!(Stats::from_json(&json), Some(stats));
+        }
+    }
+
+    mod bench_fn_tests {
+        use super::super::super::super::io::Input;
+        use super::super::super::advent1::Advent1Riddle1;
+        use super::super::super::advent3::Advent3Riddle1;
+        use super::super::super::RiddleError;
+        use super::super::bench;
+
+        #[test]
+        fn it_times_parsing_on_its_own() {
+            let riddle = Advent3Riddle1::new(Input::text("R8,U5,L5,D3\nU7,R6,D4,L4\n"));
+            let result = bench("3.1", &riddle, &[], 1, 3).unwrap();
+            assert_eq!((result.riddle.as_str(), result.runs), ("3.1", 3));
+            let parse = result.parse.unwrap();
+            assert!(parse.min <= result.total.min);
+            assert!(result.solve.min <= result.total.min);
+        }
+
+        #[test]
+        fn it_has_no_parse_times_for_streaming_riddles() {
+            let riddle = Advent1Riddle1::new(Input::text("12\n14\n"));
+            let result = bench("1.1", &riddle, &[], 0, 2).unwrap();
+            assert_eq!(result.parse, None);
+        }
+
+        #[test]
+        fn it_fails_with_the_riddle() {
+            let riddle = Advent1Riddle1::new(Input::text("x\n"));
+            assert!(matches!(
+                bench("1.1", &riddle, &[], 0, 2),
+                Err(RiddleError::Parse(_))
+            ));
+            assert!(bench("1.1", &Advent1Riddle1::new(Input::text("")), &[], 0, 0).is_err());
+        }
+    }
+
+    mod results_tests {
+        use super::super::super::super::testing::TempDir;
+        use super::super::{read_results, regressions, write_results, BenchResult, Stats};
+        use super::millis;
+        use std::fs;
+
+        fn result(riddle: &str, times: &[u64]) -> BenchResult {
+            let stats = Stats::of(&millis(times)).unwrap();
+            BenchResult {
+                riddle: riddle.to_string(),
+                runs: times.len(),
+                total: stats,
+                parse: None,
+                solve: stats,
+            }
+        }
+
+        #[test]
+        fn it_writes_and_reads_results() {
+            let dir = TempDir::new("bench-results-test");
+            let path = dir.join("results.json");
+            let path = path.to_str().unwrap();
+            let mut results = vec![result("1.1", &[1, 2]), result("3.2", &[5])];
+            results[1].parse = Some(Stats::of(&millis(&[3])).unwrap());
+
+            write_results(path, &results).unwrap();
+            assert_eq!(read_results(path).unwrap(), results);
+            fs::write(path, "{\"riddles\":[{\"riddle\":\"1.1\"}]}").unwrap();
+            assert!(read_results(path).is_err());
+        }
+
+        #[test]
+        fn it_finds_regressions() {
+            let baseline = vec![result("1.1", &[10]), result("1.2", &[10])];
+            let results = vec![
+                result("1.1", &[11]),
+                result("1.2", &[13]),
+                result("2.1", &[99]),
+            ];
+            let found = regressions(&results, &baseline, 20.0);
+            assert_eq!(found.len(), 1);
+            assert_eq!(found[0].riddle, "1.2");
+            assert!((found[0].slowdown() - 30.0).abs() < 1e-9);
+            assert_eq!(regressions(&results, &baseline, 5.0).len(), 2);
+        }
+    }
+}
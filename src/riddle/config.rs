@@ -0,0 +1,303 @@
+//! Where the inputs and answers of the riddles are found. The data directory
+//! is taken from `--data-dir`, the `AOC19_DATA_DIR` variable or a config
+//! file, in this order, and defaults to `./data`. Inputs are read from
+//! `<data>/input/`, or from `<data>/input/<profile>/` for a named input set,
+//! so everyone can keep their own puzzle inputs next to each other.
+
+use std::fs;
+use std::path::Path;
+
+use super::RiddleError;
+
+pub const DEFAULT_DATA_DIR: &str = "./data";
+/// The config file read if it exists and no other one is given.
+pub const DEFAULT_CONFIG: &str = "./aoc19.conf";
+pub const DATA_DIR_VAR: &str = "AOC19_DATA_DIR";
+pub const PROFILE_VAR: &str = "AOC19_PROFILE";
+pub const CONFIG_VAR: &str = "AOC19_CONFIG";
+
+/// Settings from one source. Those left out are taken from the next one.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Settings {
+    pub data_dir: Option<String>,
+    pub profile: Option<String>,
+}
+
+impl Settings {
+    /// Parses a config file with one `key = value` per line, where the keys
+    /// are `data-dir` and `profile`. Blank lines and lines starting with `#`
+    /// are skipped. `file` names the config in errors.
+    pub fn parse(text: &str, file: &str) -> Result<Settings, RiddleError> {
+        let mut settings = Settings::default();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid =
+                |message: String| RiddleError::Generic(format!("{}:{}: {}", file, i + 1, message));
+            let (key, value) = line
+                .split_once('=')
+                .map(|(key, value)| (key.trim(), value.trim().to_string()))
+                .filter(|(_, value)| !value.is_empty())
+                .ok_or_else(|| invalid(format!("expected 'key = value', got '{}'", line)))?;
+            match key {
+                "data-dir" => settings.data_dir = Some(value),
+                "profile" => settings.profile = Some(value),
+                _ => return Err(invalid(format!("unknown setting '{}'", key))),
+            }
+        }
+        Ok(settings)
+    }
+
+    /// Reads a config file. A relative data directory is taken relative to
+    /// the directory of the file.
+    pub fn from_file(path: &str) -> Result<Settings, RiddleError> {
+        let text = fs::read_to_string(path).map_err(|err| {
+            RiddleError::IO(std::io::Error::new(
+                err.kind(),
+                format!("cannot read the config in {}: {}", path, err),
+            ))
+        })?;
+        let mut settings = Settings::parse(&text, path)?;
+        if let (Some(data_dir), Some(dir)) = (&settings.data_dir, Path::new(path).parent()) {
+            settings.data_dir = Some(dir.join(data_dir).to_string_lossy().into_owned());
+        }
+        Ok(settings)
+    }
+
+    /// The settings of the environment, looked up with `env`.
+    pub fn from_env(env: &dyn Fn(&str) -> Option<String>) -> Settings {
+        Settings {
+            data_dir: env(DATA_DIR_VAR),
+            profile: env(PROFILE_VAR),
+        }
+    }
+
+    /// These settings, with those left out taken from `other`.
+    pub fn or(self, other: Settings) -> Settings {
+        Settings {
+            data_dir: self.data_dir.or(other.data_dir),
+            profile: self.profile.or(other.profile),
+        }
+    }
+}
+
+/// The data directory and the input set in use.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Config {
+    pub data_dir: String,
+    /// The name of the input set, or `None` for the inputs right in
+    /// `<data>/input/`.
+    pub profile: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            data_dir: DEFAULT_DATA_DIR.to_string(),
+            profile: None,
+        }
+    }
+}
+
+impl Config {
+    /// Combines the settings of the command line with those of the
+    /// environment and of the config file. The config file is `config`, or
+    /// the one named by `AOC19_CONFIG`, both of which must exist, or else
+    /// `default_config`, usually `DEFAULT_CONFIG`, if it exists. Variables
+    /// that are set but empty count as unset.
+    pub fn load(
+        flags: Settings,
+        config: Option<&str>,
+        default_config: Option<&str>,
+        env: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Config, RiddleError> {
+        let env = |name: &str| env(name).filter(|value| !value.is_empty());
+        let file = match config.map(str::to_string).or_else(|| env(CONFIG_VAR)) {
+            Some(path) => Settings::from_file(&path)?,
+            None => match default_config.filter(|path| Path::new(path).is_file()) {
+                Some(path) => Settings::from_file(path)?,
+                None => Settings::default(),
+            },
+        };
+        let settings = flags.or(Settings::from_env(&env)).or(file);
+        if let Some(profile) = &settings.profile {
+            if !is_profile(profile) {
+                return Err(RiddleError::Generic(format!(
+                    "invalid profile '{}', expected a name of letters, digits, - and _",
+                    profile
+                )));
+            }
+        }
+        Ok(Config {
+            data_dir: settings
+                .data_dir
+                .unwrap_or_else(|| DEFAULT_DATA_DIR.to_string()),
+            profile: settings.profile,
+        })
+    }
+
+    /// The path of an input file, like `1.txt`, in the input set.
+    pub fn input_path(&self, file: &str) -> String {
+        let mut path = Path::new(&self.data_dir).join("input");
+        if let Some(profile) = &self.profile {
+            path.push(profile);
+        }
+        path.join(file).to_string_lossy().into_owned()
+    }
+
+    /// The answers `verify` checks: `<data>/answers/<profile>.txt`, or
+    /// `default.txt` without a profile.
+    pub fn answers_path(&self) -> String {
+        let name = self.profile.as_deref().unwrap_or("default");
+        Path::new(&self.data_dir)
+            .join("answers")
+            .join(format!("{}.txt", name))
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// Profiles name a directory, so they must not reach outside of it.
+fn is_profile(profile: &str) -> bool {
+    !profile.is_empty()
+        && profile
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod config_tests {
+    mod settings_tests {
+        use super::super::super::RiddleError;
+        use super::super::Settings;
+
+        #[test]
+        fn it_parses_config_files() {
+            let text = "# mine\ndata-dir = /srv/aoc\n\nprofile=alice\n";
+            assert_eq!(
+                Settings::parse(text, "aoc19.conf").unwrap(),
+                Settings {
+                    data_dir: Some("/srv/aoc".to_string()),
+                    profile: Some("alice".to_string()),
+                }
+            );
+        }
+
+        #[test]
+        fn it_rejects_invalid_lines() {
+            for (text, message) in &[
+                (
+                    "data-dir",
+                    "aoc19.conf:1: expected 'key = value', got 'data-dir'",
+                ),
+                ("\ncolor = red", "aoc19.conf:2: unknown setting 'color'"),
+            ] {
+                match Settings::parse(text, "aoc19.conf") {
+                    Err(RiddleError::Generic(err)) => assert_eq!(err, *message),
+                    other => panic!("expected an error, got {:?}", other),
+                }
+            }
+        }
+
+        #[test]
+        fn it_prefers_the_first_settings() {
+            let flags = Settings {
+                data_dir: None,
+                profile: Some("bob".to_string()),
+            };
+            let file = Settings {
+                data_dir: Some("/srv/aoc".to_string()),
+                profile: Some("alice".to_string()),
+            };
+            let settings = flags.or(file);
+            assert_eq!(settings.data_dir.unwrap(), "/srv/aoc");
+            assert_eq!(settings.profile.unwrap(), "bob");
+        }
+    }
+
+    mod load_tests {
+        use std::fs;
+
+        use super::super::super::super::testing::TempDir;
+        use super::super::super::RiddleError;
+        use super::super::{Config, Settings, CONFIG_VAR, DATA_DIR_VAR, PROFILE_VAR};
+
+        fn no_env(_: &str) -> Option<String> {
+            None
+        }
+
+        #[test]
+        fn it_defaults_to_the_data_directory() {
+            let config = Config::load(Settings::default(), None, None, &no_env).unwrap();
+            assert_eq!(config, Config::default());
+            assert_eq!(config.input_path("1.txt"), "./data/input/1.txt");
+            assert_eq!(config.answers_path(), "./data/answers/default.txt");
+        }
+
+        #[test]
+        fn it_prefers_flags_to_the_environment() {
+            let env = |name: &str| match name {
+                DATA_DIR_VAR => Some("/env".to_string()),
+                PROFILE_VAR => Some("alice".to_string()),
+                _ => None,
+            };
+            let config = Config::load(Settings::default(), None, None, &env).unwrap();
+            assert_eq!(config.input_path("2.txt"), "/env/input/alice/2.txt");
+            assert_eq!(config.answers_path(), "/env/answers/alice.txt");
+
+            let flags = Settings {
+                data_dir: Some("/flag".to_string()),
+                profile: None,
+            };
+            let config = Config::load(flags, None, None, &env).unwrap();
+            assert_eq!(config.input_path("2.txt"), "/flag/input/alice/2.txt");
+        }
+
+        #[test]
+        fn it_ignores_empty_variables() {
+            let env = |name: &str| match name {
+                DATA_DIR_VAR | PROFILE_VAR | CONFIG_VAR => Some(String::new()),
+                _ => None,
+            };
+            let config = Config::load(Settings::default(), None, None, &env).unwrap();
+            assert_eq!(config, Config::default());
+        }
+
+        #[test]
+        fn it_reads_the_config_file() {
+            let dir = TempDir::new("config-test");
+            let path = dir.join("aoc19.conf");
+            fs::write(&path, "data-dir = inputs\nprofile = bob\n").unwrap();
+
+            let config = Config::load(Settings::default(), path.to_str(), None, &no_env).unwrap();
+            let expected = dir.join("inputs").join("input").join("bob").join("3.txt");
+            assert_eq!(config.input_path("3.txt"), expected.to_str().unwrap());
+
+            let config = Config::load(Settings::default(), None, path.to_str(), &no_env).unwrap();
+            assert_eq!(config.input_path("3.txt"), expected.to_str().unwrap());
+            let missing = Some("./does/not/exist");
+            let config = Config::load(Settings::default(), None, missing, &no_env).unwrap();
+            assert_eq!(config, Config::default());
+        }
+
+        #[test]
+        fn it_rejects_missing_configs_and_invalid_profiles() {
+            let missing =
+                Config::load(Settings::default(), Some("./does/not/exist"), None, &no_env);
+            assert!(matches!(missing, Err(RiddleError::IO(_))));
+
+            for profile in &["", "..", "a/b"] {
+                let flags = Settings {
+                    data_dir: None,
+                    profile: Some(profile.to_string()),
+                };
+                assert!(matches!(
+                    Config::load(flags, None, None, &no_env),
+                    Err(RiddleError::Generic(_))
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! Riddles split into a parse step and a solve step. A `Day` parses its
+//! input into a typed value once, and both of its parts solve over that
+//! value.
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::super::io::Input;
+use super::{timed, Riddle, RiddleError, Solution, Timings};
+
+/// The two riddles of a day over a shared, parsed input.
+pub trait Day: 'static {
+    /// The input as both parts need it.
+    type Parsed: Send + Sync + 'static;
+
+    fn parse(input: &Input) -> Result<Self::Parsed, RiddleError>;
+
+    fn part1(parsed: &Self::Parsed, args: &[String]) -> Result<Solution, RiddleError>;
+
+    fn part2(parsed: &Self::Parsed, args: &[String]) -> Result<Solution, RiddleError>;
+}
+
+/// The parsed input of a day, once it has been parsed.
+type ParsedInput<D> = Arc<Mutex<Option<Arc<<D as Day>::Parsed>>>>;
+
+/// The parsed inputs of the days whose riddles are built with `build`, so
+/// that both parts of a day parse the input only once. Riddles built with
+/// the same `SharedInputs` must be given the same input.
+#[derive(Default)]
+pub struct SharedInputs {
+    inputs: HashMap<TypeId, Box<dyn Any + Send>>,
+}
+
+impl SharedInputs {
+    fn get<D: Day>(&mut self) -> ParsedInput<D> {
+        self.inputs
+            .entry(TypeId::of::<D>())
+            .or_insert_with(|| Box::new(ParsedInput::<D>::default()))
+            .downcast_ref::<ParsedInput<D>>()
+            .expect("parsed inputs are stored by the type of their day")
+            .clone()
+    }
+}
+
+/// Part `PART` of day `D`.
+pub struct DayRiddle<D: Day, const PART: u8> {
+    input: Input,
+    parsed: ParsedInput<D>,
+}
+
+impl<D: Day, const PART: u8> DayRiddle<D, PART> {
+    /// A riddle that parses its input on its own.
+    pub fn new<I: Into<Input>>(input: I) -> DayRiddle<D, PART> {
+        DayRiddle::build(input.into(), &mut SharedInputs::default())
+    }
+
+    /// A riddle that shares the parsed input with the other part of its day.
+    pub fn build(input: Input, shared: &mut SharedInputs) -> DayRiddle<D, PART> {
+        assert!(PART == 1 || PART == 2, "a day has parts 1 and 2");
+        DayRiddle {
+            input,
+            parsed: shared.get::<D>(),
+        }
+    }
+
+    /// Parses the input unless it has already been parsed, along with how
+    /// long parsing took if this call did it. Failures are not kept, so the
+    /// next call parses again.
+    fn parsed(&self) -> Result<(Arc<D::Parsed>, Option<Duration>), RiddleError> {
+        let mut parsed = self.parsed.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(parsed) = parsed.as_ref() {
+            return Ok((parsed.clone(), None));
+        }
+        let start = Instant::now();
+        let input = Arc::new(parse::<D>(&self.input)?);
+        let elapsed = start.elapsed();
+        *parsed = Some(input.clone());
+        Ok((input, Some(elapsed)))
+    }
+
+    fn solve_parsed(parsed: &D::Parsed, args: &[String]) -> Result<Solution, RiddleError> {
+        if PART == 1 {
+            D::part1(parsed, args)
+        } else {
+            D::part2(parsed, args)
+        }
+    }
+}
+
+fn parse<D: Day>(input: &Input) -> Result<D::Parsed, RiddleError> {
+    D::parse(input).map_err(|err| RiddleError::Parse(Box::new(err)))
+}
+
+impl<D: Day, const PART: u8> Riddle for DayRiddle<D, PART> {
+    fn solve(&self, args: &[String]) -> Result<Solution, RiddleError> {
+        Self::solve_parsed(&self.parsed()?.0, args)
+    }
+
+    /// Times parsing only if this part is the first to parse the shared
+    /// input, and leaves the time of parsing out otherwise.
+    fn solve_timed(&self, args: &[String]) -> Result<(Solution, Timings), RiddleError> {
+        let (parsed, parse) = self.parsed()?;
+        let start = Instant::now();
+        let solution = Self::solve_parsed(&parsed, args)?;
+        let timings = Timings {
+            parse,
+            solve: start.elapsed(),
+        };
+        Ok((solution, timings))
+    }
+
+    /// Parses the input anew, even if it has been parsed before, so both
+    /// steps are timed.
+    fn solve_timed_anew(&self, args: &[String]) -> Result<(Solution, Timings), RiddleError> {
+        timed(
+            || parse::<D>(&self.input),
+            |parsed| Self::solve_parsed(&parsed, args),
+        )
+    }
+}
+
+#[cfg(test)]
+mod day_tests {
+    /// Counts how often it parses, so each test needs a day of its own.
+    macro_rules! counting_day {
+        ($day:ident, $parses:ident) => {
+            static $parses: AtomicUsize = AtomicUsize::new(0);
+
+            struct $day;
+
+            impl Day for $day {
+                type Parsed = Vec<i64>;
+
+                fn parse(input: &Input) -> Result<Vec<i64>, RiddleError> {
+                    $parses.fetch_add(1, Ordering::SeqCst);
+                    let mut text = String::new();
+                    input.open()?.read_to_string(&mut text)?;
+                    text.split(',')
+                        .map(|n| n.parse().map_err(|_| RiddleError::Generic(n.to_string())))
+                        .collect()
+                }
+
+                fn part1(parsed: &Vec<i64>, _: &[String]) -> Result<Solution, RiddleError> {
+                    Ok(Solution::Number(parsed.iter().sum()))
+                }
+
+                fn part2(parsed: &Vec<i64>, _: &[String]) -> Result<Solution, RiddleError> {
+                    Ok(Solution::Number(parsed.iter().product()))
+                }
+            }
+        };
+    }
+
+    mod solve_tests {
+        use std::io::Read;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        use super::super::super::super::io::Input;
+        use super::super::super::{Riddle, RiddleError, Solution};
+        use super::super::{Day, DayRiddle, SharedInputs};
+
+        counting_day!(Shared, SHARED_PARSES);
+        counting_day!(Separate, SEPARATE_PARSES);
+        counting_day!(Invalid, INVALID_PARSES);
+        counting_day!(Timed, TIMED_PARSES);
+        counting_day!(SharedTimed, SHARED_TIMED_PARSES);
+
+        #[test]
+        fn it_parses_once_for_both_parts() {
+            let mut shared = SharedInputs::default();
+            let part1 = DayRiddle::<Shared, 1>::build(Input::text("2,3,4"), &mut shared);
+            let part2 = DayRiddle::<Shared, 2>::build(Input::text("2,3,4"), &mut shared);
+
+            assert_eq!(part1.solve(&[]).unwrap(), Solution::Number(9));
+            assert_eq!(part2.solve(&[]).unwrap(), Solution::Number(24));
+            assert_eq!(part1.solve(&[]).unwrap(), Solution::Number(9));
+            assert_eq!(SHARED_PARSES.load(Ordering::SeqCst), 1);
+        }
+
+        #[test]
+        fn it_parses_separately_without_shared_inputs() {
+            let part1 = DayRiddle::<Separate, 1>::new(Input::text("2,3"));
+            let part2 = DayRiddle::<Separate, 2>::new(Input::text("2,3"));
+            part1.solve(&[]).unwrap();
+            part2.solve(&[]).unwrap();
+            assert_eq!(SEPARATE_PARSES.load(Ordering::SeqCst), 2);
+        }
+
+        #[test]
+        fn it_reports_parse_errors_on_their_own() {
+            let riddle = DayRiddle::<Invalid, 1>::new(Input::text("2,x"));
+            for _ in 0..2 {
+                match riddle.solve(&[]) {
+                    Err(RiddleError::Parse(err)) => {
+                        assert!(matches!(*err, RiddleError::Generic(ref n) if n == "x"))
+                    }
+                    other => panic!("expected a parse error, got {:?}", other),
+                }
+            }
+            assert_eq!(INVALID_PARSES.load(Ordering::SeqCst), 2);
+            assert!(matches!(
+                riddle.solve_timed(&[]),
+                Err(RiddleError::Parse(_))
+            ));
+        }
+
+        #[test]
+        fn it_times_both_steps() {
+            let riddle = DayRiddle::<Timed, 2>::new(Input::text("5,6"));
+            riddle.solve(&[]).unwrap();
+            let (solution, timings) = riddle.solve_timed_anew(&[]).unwrap();
+            assert_eq!(solution, Solution::Number(30));
+            assert!(timings.parse.is_some());
+            assert_eq!(TIMED_PARSES.load(Ordering::SeqCst), 2);
+        }
+
+        #[test]
+        fn it_times_the_shared_parse_once() {
+            let mut shared = SharedInputs::default();
+            let part1 = DayRiddle::<SharedTimed, 1>::build(Input::text("5,6"), &mut shared);
+            let part2 = DayRiddle::<SharedTimed, 2>::build(Input::text("5,6"), &mut shared);
+
+            let (solution, timings) = part2.solve_timed(&[]).unwrap();
+            assert_eq!(solution, Solution::Number(30));
+            assert!(timings.parse.is_some());
+            let (solution, timings) = part1.solve_timed(&[]).unwrap();
+            assert_eq!(solution, Solution::Number(11));
+            assert_eq!(timings.parse, None);
+            assert_eq!(SHARED_TIMED_PARSES.load(Ordering::SeqCst), 1);
+        }
+    }
+}
@@ -1,29 +1,251 @@
+use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt;
-use std::io::ErrorKind;
+use std::time::{Duration, Instant};
 
-pub mod advent1;
-pub mod advent2;
-pub mod advent3;
+use super::bigint::BigInt;
+use super::geometry::{Grid, Point};
+use super::io::{Input, ParseError, ParseErrorKind};
+use super::json::Json;
+use args::ArgSchema;
+pub use day::{Day, DayRiddle, SharedInputs};
+
+/// Registers the riddles of a day. Each riddle names its type, which needs a
+/// `build` constructor taking the input and the `SharedInputs`, like
+/// `DayRiddle`, followed by its day, part, name and default input:
+///
+/// ```ignore
+/// riddles! {
+///     Advent1Riddle1 {
+///         day: 1,
+///         part: 1,
+///         name: "...",
+///         input: "./data/input/1.txt",
+///         args: FUEL_ARGS,
+///     },
+/// }
+/// ```
+macro_rules! riddles {
+    ($($riddle:ident {
+        day: $day:literal,
+        part: $part:literal,
+        name: $name:literal,
+        input: $input:literal,
+        args: $args:expr $(,)?
+    }),* $(,)?) => {
+        /// The riddles of this day.
+        pub const RIDDLES: &[super::RiddleEntry] = &[$(super::RiddleEntry {
+            day: $day,
+            part: $part,
+            name: $name,
+            default_input: $input,
+            args: &$args,
+            build: |input, shared| Box::new($riddle::build(input, shared)),
+        }),*];
+    };
+}
+
+/// Declares the modules of the days, whose `RIDDLES` are then found by
+/// `registered_riddles`.
+macro_rules! days {
+    ($($day:ident),* $(,)?) => {
+        $(pub mod $day;)*
+
+        /// The riddles of all days, in the order the days are declared.
+        pub fn registered_riddles() -> impl Iterator<Item = &'static RiddleEntry> {
+            const DAYS: &[&[RiddleEntry]] = &[$($day::RIDDLES),*];
+            DAYS.iter().flat_map(|riddles| riddles.iter())
+        }
+    };
+}
+
+days!(advent1, advent2, advent3);
+
+pub mod answers;
+pub mod args;
+pub mod bench;
+pub mod config;
+pub mod day;
+pub mod ocr;
 pub mod riddlecontainer;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Solution {
     Number(i64),
+    /// A number that does not fit into an `i64`.
+    BigNumber(BigInt),
+    Text(String),
+    /// An image with `true` for lit pixels. It is shown as the letters it
+    /// spells if they can be read, or else drawn with `#` and `.`.
+    Grid(Grid<bool>),
+    Point(Point),
+    Pair(Box<Solution>, Box<Solution>),
+    /// An answer with notes about how it was found. Only the answer is
+    /// shown, but equality compares the notes too, so compare `answer()`
+    /// to ignore them.
+    Noted {
+        answer: Box<Solution>,
+        notes: Notes,
+    },
+}
+
+/// What a riddle has to say besides its answer.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Notes {
+    /// A report the riddle was asked for, like the fuel per module of day 1.
+    pub report: Option<String>,
+    /// Problems with the input that did not stop the riddle, like lines
+    /// that were skipped.
+    pub warnings: Vec<String>,
+}
+
+impl Notes {
+    pub fn is_empty(&self) -> bool {
+        self.report.is_none() && self.warnings.is_empty()
+    }
+}
+
+impl Solution {
+    pub fn pair(first: Solution, second: Solution) -> Solution {
+        Solution::Pair(Box::new(first), Box::new(second))
+    }
+
+    /// The solution with `notes`, unless there are none.
+    pub fn with_notes(self, notes: Notes) -> Solution {
+        if notes.is_empty() {
+            return self;
+        }
+        Solution::Noted {
+            answer: Box::new(self),
+            notes,
+        }
+    }
+
+    /// The solution without its notes.
+    pub fn answer(&self) -> &Solution {
+        match self {
+            Solution::Noted { answer, .. } => answer.answer(),
+            _ => self,
+        }
+    }
+
+    /// The notes of the solution, if it has any.
+    pub fn notes(&self) -> Option<&Notes> {
+        match self {
+            Solution::Noted { notes, .. } => Some(notes),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Solution {
+    /// Writes the answer as it is entered on the website.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Solution::Number(n) => write!(f, "{}", n),
+            Solution::BigNumber(n) => write!(f, "{}", n),
+            Solution::Text(text) => write!(f, "{}", text),
+            Solution::Grid(image) => match ocr::read_letters(image) {
+                Some(letters) => write!(f, "{}", letters),
+                None => {
+                    let rows: Vec<String> = image
+                        .rows()
+                        .map(|row| row.iter().map(|&lit| if lit { '#' } else { '.' }).collect())
+                        .collect();
+                    write!(f, "{}", rows.join("\n"))
+                }
+            },
+            Solution::Point(point) => write!(f, "{}", point),
+            Solution::Pair(first, second) => write!(f, "{},{}", first, second),
+            Solution::Noted { answer, .. } => write!(f, "{}", answer),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum RiddleError {
     UnknownRiddle(String),
     IO(std::io::Error),
+    /// A riddle got positional arguments: the number it expects and the
+    /// number it got.
     ArgumentsCount(u8, u8),
+    /// The options of a riddle are invalid. The usage lists the options the
+    /// riddle accepts.
+    InvalidArguments {
+        message: String,
+        usage: String,
+    },
     Generic(String),
     NoSolutionFound,
+    /// The input could not be read or parsed, before solving started.
+    Parse(Box<RiddleError>),
+    /// There is no input file at the path of the riddle in the input set.
+    MissingInput(String),
+}
+
+impl RiddleError {
+    /// A name of the variant that scripts can match on, like `io`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RiddleError::UnknownRiddle(_) => "unknown-riddle",
+            RiddleError::IO(_) => "io",
+            RiddleError::ArgumentsCount(_, _) => "arguments-count",
+            RiddleError::InvalidArguments { .. } => "invalid-arguments",
+            RiddleError::Generic(_) => "generic",
+            RiddleError::NoSolutionFound => "no-solution",
+            RiddleError::Parse(_) => "parse",
+            RiddleError::MissingInput(_) => "missing-input",
+        }
+    }
+
+    /// The error as a JSON object with its kind and message. Invalid
+    /// arguments add the usage, parse errors the error they wrap, and
+    /// missing inputs their path.
+    pub fn to_json(&self) -> Json {
+        let kind = ("kind", Json::string(self.kind()));
+        match self {
+            RiddleError::InvalidArguments { message, usage } => Json::object(vec![
+                kind,
+                ("message", Json::string(message.as_str())),
+                ("usage", Json::string(usage.as_str())),
+            ]),
+            RiddleError::Parse(err) => Json::object(vec![
+                kind,
+                ("message", Json::string(self.to_string())),
+                ("cause", err.to_json()),
+            ]),
+            RiddleError::MissingInput(path) => Json::object(vec![
+                kind,
+                ("message", Json::string(self.to_string())),
+                ("path", Json::string(path.as_str())),
+            ]),
+            _ => Json::object(vec![kind, ("message", Json::string(self.to_string()))]),
+        }
+    }
 }
 
 impl fmt::Display for RiddleError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            RiddleError::UnknownRiddle(riddle) => write!(f, "unknown riddle '{}'", riddle),
+            RiddleError::IO(err) => write!(f, "{}", err),
+            RiddleError::ArgumentsCount(expected, given) => write!(
+                f,
+                "expected {} positional arguments, got {}",
+                expected, given
+            ),
+            RiddleError::InvalidArguments { message, usage } => {
+                write!(f, "{}\n\noptions:\n{}", message, usage)
+            }
+            RiddleError::Generic(message) => write!(f, "{}", message),
+            RiddleError::NoSolutionFound => write!(f, "no solution found"),
+            RiddleError::Parse(err) => write!(f, "cannot parse the input: {}", err),
+            RiddleError::MissingInput(path) => write!(
+                f,
+                "missing input {}, save your puzzle input there or choose another data directory or profile",
+                path
+            ),
+        }
     }
 }
 
@@ -31,7 +253,7 @@ impl Error for RiddleError {}
 
 impl std::convert::From<RiddleError> for std::io::Error {
     fn from(err: RiddleError) -> Self {
-        std::io::Error::new(ErrorKind::Other, err)
+        std::io::Error::other(err)
     }
 }
 
@@ -41,6 +263,236 @@ impl std::convert::From<std::io::Error> for RiddleError {
     }
 }
 
-pub trait Riddle {
+impl<E: fmt::Debug> std::convert::From<ParseError<E>> for RiddleError {
+    fn from(err: ParseError<E>) -> Self {
+        match err.kind {
+            // Keeps the kind of the error, but names the input it came from.
+            ParseErrorKind::IO(io_err) => RiddleError::IO(std::io::Error::new(
+                io_err.kind(),
+                format!("{}: {}", err.file, io_err),
+            )),
+            _ => RiddleError::Generic(err.to_string()),
+        }
+    }
+}
+
+/// A riddle. Riddles are `Sync` so that several of them can be solved in
+/// parallel.
+pub trait Riddle: Sync {
     fn solve(&self, args: &[String]) -> Result<Solution, RiddleError>;
+
+    /// Solves the riddle and measures how long it took. Riddles that parse
+    /// their input in a separate step, like `DayRiddle`, override this to
+    /// time parsing on its own, usually with `timed`.
+    fn solve_timed(&self, args: &[String]) -> Result<(Solution, Timings), RiddleError> {
+        let start = Instant::now();
+        let solution = self.solve(args)?;
+        let timings = Timings {
+            parse: None,
+            solve: start.elapsed(),
+        };
+        Ok((solution, timings))
+    }
+
+    /// Solves the riddle like `solve_timed`, but without anything kept from
+    /// earlier runs, so that benchmarks time every step of every run.
+    fn solve_timed_anew(&self, args: &[String]) -> Result<(Solution, Timings), RiddleError> {
+        self.solve_timed(args)
+    }
+}
+
+/// How long the steps of solving a riddle took.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Timings {
+    /// Reading and parsing the input, if the riddle does that in a step of
+    /// its own.
+    pub parse: Option<Duration>,
+    /// Solving, including parsing if that is not a step of its own.
+    pub solve: Duration,
+}
+
+impl Timings {
+    pub fn total(&self) -> Duration {
+        self.parse.unwrap_or_default() + self.solve
+    }
+
+    /// The timings in nanoseconds, with a `null` parse time if parsing is
+    /// not a step of its own.
+    pub fn to_json(&self) -> Json {
+        Json::object(vec![
+            ("parse_ns", self.parse.map_or(Json::Null, nanos)),
+            ("solve_ns", nanos(self.solve)),
+            ("total_ns", nanos(self.total())),
+        ])
+    }
+}
+
+fn nanos(duration: Duration) -> Json {
+    Json::Int(i64::try_from(duration.as_nanos()).unwrap_or(i64::MAX))
+}
+
+/// Parses the input with `parse`, solves it with `solve` and times both.
+pub fn timed<T, P, S>(parse: P, solve: S) -> Result<(Solution, Timings), RiddleError>
+where
+    P: FnOnce() -> Result<T, RiddleError>,
+    S: FnOnce(T) -> Result<Solution, RiddleError>,
+{
+    let start = Instant::now();
+    let input = parse()?;
+    let parsed = Instant::now();
+    let solution = solve(input)?;
+    let timings = Timings {
+        parse: Some(parsed - start),
+        solve: parsed.elapsed(),
+    };
+    Ok((solution, timings))
+}
+
+/// A riddle as registered by its day with `riddles!`.
+#[derive(Debug)]
+pub struct RiddleEntry {
+    pub day: u8,
+    pub part: u8,
+    pub name: &'static str,
+    /// The input file of the riddle in an input set, like `1.txt`, see
+    /// `config::Config::input_path`.
+    pub default_input: &'static str,
+    /// The options the riddle accepts.
+    pub args: &'static ArgSchema,
+    /// Builds the riddle for an input. Riddles built with the same
+    /// `SharedInputs` share the parsed input of their day.
+    pub build: fn(Input, &mut SharedInputs) -> Box<dyn Riddle>,
+}
+
+impl RiddleEntry {
+    /// The key the riddle is selected by, for example `1.2`.
+    pub fn key(&self) -> String {
+        format!("{}.{}", self.day, self.part)
+    }
+}
+
+#[cfg(test)]
+mod riddle_tests {
+    mod riddle_error_tests {
+        use super::super::super::json::Json;
+        use super::super::RiddleError;
+
+        #[test]
+        fn it_describes_errors() {
+            let io = std::io::Error::other("./data/input/9.txt: not found");
+            let errors = [
+                (
+                    RiddleError::UnknownRiddle("9.1".to_string()),
+                    "unknown riddle '9.1'",
+                ),
+                (
+                    RiddleError::ArgumentsCount(0, 2),
+                    "expected 0 positional arguments, got 2",
+                ),
+                (RiddleError::NoSolutionFound, "no solution found"),
+                (
+                    RiddleError::Parse(Box::new(RiddleError::IO(io))),
+                    "cannot parse the input: ./data/input/9.txt: not found",
+                ),
+                (
+                    RiddleError::MissingInput("./data/input/bob/9.txt".to_string()),
+                    "missing input ./data/input/bob/9.txt, save your puzzle input there or choose another data directory or profile",
+                ),
+            ];
+            for (err, message) in &errors {
+                assert_eq!(err.to_string(), *message);
+            }
+            assert_eq!(errors[3].0.kind(), "parse");
+            assert_eq!(errors[4].0.kind(), "missing-input");
+        }
+
+        #[test]
+        fn it_converts_errors_to_json() {
+            let err = RiddleError::Parse(Box::new(RiddleError::InvalidArguments {
+                message: "unknown option 'x'".to_string(),
+                usage: "  (no options)\n".to_string(),
+            }));
+            let json = err.to_json();
+            assert_eq!(json.get("kind").and_then(Json::as_str), Some("parse"));
+            let cause = json.get("cause").unwrap();
+            assert_eq!(
+                cause.get("message").and_then(Json::as_str),
+                Some("unknown option 'x'")
+            );
+            assert_eq!(
+                cause.get("usage").and_then(Json::as_str),
+                Some("  (no options)\n")
+            );
+
+            let json = RiddleError::MissingInput("./data/input/9.txt".to_string()).to_json();
+            assert_eq!(
+                json.get("path").and_then(Json::as_str),
+                Some("./data/input/9.txt")
+            );
+        }
+    }
+
+    mod solution_tests {
+        use super::super::super::bigint::BigInt;
+        use super::super::super::geometry::{Grid, Point};
+        use super::super::{Notes, Solution};
+
+        fn image(rows: &[&str]) -> Grid<bool> {
+            Grid::from_rows(
+                rows.iter()
+                    .map(|row| row.chars().map(|c| c == '#').collect())
+                    .collect(),
+            )
+            .unwrap()
+        }
+
+        #[test]
+        fn it_writes_answers() {
+            let big: BigInt = "123456789012345678901234567890".parse().unwrap();
+            assert_eq!(Solution::Number(-42).to_string(), "-42");
+            assert_eq!(
+                Solution::BigNumber(big).to_string(),
+                "123456789012345678901234567890"
+            );
+            assert_eq!(Solution::Text("abc".to_string()).to_string(), "abc");
+            assert_eq!(Solution::Point(Point::new(3, -1)).to_string(), "3,-1");
+            let pair = Solution::pair(Solution::Number(1), Solution::Point(Point::new(2, 3)));
+            assert_eq!(pair.to_string(), "1,2,3");
+        }
+
+        #[test]
+        fn it_keeps_notes_apart_from_the_answer() {
+            let notes = Notes {
+                report: None,
+                warnings: vec!["skipped 1 invalid line(s) in 1.txt".to_string()],
+            };
+            let noted = Solution::Number(7).with_notes(notes.clone());
+            assert_eq!(noted.to_string(), "7");
+            assert_eq!(noted.answer(), &Solution::Number(7));
+            assert_eq!(noted.notes(), Some(&notes));
+            assert_eq!(
+                Solution::Number(7).with_notes(Notes::default()),
+                Solution::Number(7)
+            );
+        }
+
+        #[test]
+        fn it_reads_the_letters_of_images() {
+            let rows = [
+                ".##..###..",
+                "#..#.#..#.",
+                "#..#.###..",
+                "####.#..#.",
+                "#..#.#..#.",
+                "#..#.###..",
+            ];
+            assert_eq!(Solution::Grid(image(&rows)).to_string(), "AB");
+        }
+
+        #[test]
+        fn it_draws_images_it_cannot_read() {
+            let rows = ["#.#", ".#."];
+            assert_eq!(Solution::Grid(image(&rows)).to_string(), "#.#\n.#.");
+        }
+    }
 }
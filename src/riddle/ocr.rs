@@ -0,0 +1,99 @@
+//! Reads the block letters some riddles draw as their answer. The letters
+//! are 4 pixels wide and 6 high, separated by a blank column.
+
+use super::super::geometry::Grid;
+
+const LETTER_WIDTH: usize = 4;
+const LETTER_HEIGHT: usize = 6;
+
+/// The known letters, drawn with `#` for a lit pixel.
+const LETTERS: &[(char, [&str; LETTER_HEIGHT])] = &[
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// Reads the letters of an image with `true` for lit pixels. Blank columns
+/// on the left are skipped. Returns `None` unless the image is 6 pixels
+/// high and every letter in it is known.
+pub fn read_letters(image: &Grid<bool>) -> Option<String> {
+    if image.height() != LETTER_HEIGHT {
+        return None;
+    }
+    let rows: Vec<&[bool]> = image.rows().collect();
+    let lit = |x: usize| rows.iter().any(|row| row.get(x) == Some(&true));
+    let start = (0..image.width()).find(|&x| lit(x))?;
+
+    let mut text = String::new();
+    let mut x = start;
+    while (x..image.width()).any(lit) {
+        let letter = LETTERS.iter().find(|(_, pixels)| {
+            pixels.iter().zip(&rows).all(|(pixels, row)| {
+                pixels
+                    .bytes()
+                    .enumerate()
+                    .all(|(dx, pixel)| (pixel == b'#') == (row.get(x + dx) == Some(&true)))
+            })
+        })?;
+        if lit(x + LETTER_WIDTH) {
+            return None;
+        }
+        text.push(letter.0);
+        x += LETTER_WIDTH + 1;
+    }
+    Some(text)
+}
+
+#[cfg(test)]
+mod ocr_tests {
+    mod read_letters_tests {
+        use super::super::super::super::geometry::Grid;
+        use super::super::read_letters;
+
+        fn image(rows: &[&str]) -> Grid<bool> {
+            Grid::from_rows(
+                rows.iter()
+                    .map(|row| row.chars().map(|c| c == '#').collect())
+                    .collect(),
+            )
+            .unwrap()
+        }
+
+        #[test]
+        fn it_reads_letters() {
+            let rows = [
+                "..#..#.####.###..###..",
+                "..#..#.#....#..#.#..#.",
+                "..####.###..###..#..#.",
+                "..#..#.#....#..#.###..",
+                "..#..#.#....#..#.#.#..",
+                "..#..#.####.###..#..#.",
+            ];
+            assert_eq!(read_letters(&image(&rows)), Some("HEBR".to_string()));
+        }
+
+        #[test]
+        fn it_rejects_unknown_shapes() {
+            let rows = ["#..#", "#..#", "####", "#..#", "#..#", "#.##"];
+            assert_eq!(read_letters(&image(&rows)), None);
+            assert_eq!(read_letters(&image(&rows[..5])), None);
+            assert_eq!(read_letters(&image(&["....."; 6])), None);
+
+            let touching = ["#####", "#....", "###..", "#....", "#....", "#...."];
+            assert_eq!(read_letters(&image(&touching)), None);
+        }
+    }
+}
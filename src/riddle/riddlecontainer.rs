@@ -1,79 +1,234 @@
+use std::any::Any;
 use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use std::time::Instant;
 
-use super::advent1::{Advent1Riddle1, Advent1Riddle2};
-use super::advent2::{Advent2Riddle1, Advent2Riddle2};
-use super::advent3::{Advent3Riddle1, Advent3Riddle2};
-use super::{Riddle, RiddleError, Solution};
+use super::super::io::Input;
+use super::bench::{bench, BenchResult};
+use super::config::Config;
+use super::{
+    registered_riddles, Riddle, RiddleEntry, RiddleError, SharedInputs, Solution, Timings,
+};
 
+/// All registered riddles, built once with their default inputs.
 pub struct RiddleContainer {
-    advent1_riddle1: Advent1Riddle1,
-    advent1_riddle2: Advent1Riddle2,
-    advent2_riddle1: Advent2Riddle1,
-    advent2_riddle2: Advent2Riddle2,
-    advent3_riddle1: Advent3Riddle1,
-    advent3_riddle2: Advent3Riddle2,
+    config: Config,
+    riddles: Vec<(&'static RiddleEntry, Box<dyn Riddle>)>,
+    /// The paths of the default inputs, in the order of the riddles.
+    inputs: Vec<String>,
+    by_key: HashMap<String, usize>,
+}
+
+/// The outcome of solving a riddle with `solve_all`.
+#[derive(Debug)]
+pub struct RiddleRun {
+    pub entry: &'static RiddleEntry,
+    pub result: Result<Solution, RiddleError>,
+    /// Parsing is timed on its own only for the run that parsed the input
+    /// shared by the riddles of a day.
+    pub timings: Timings,
 }
 
 impl RiddleContainer {
     pub fn new() -> RiddleContainer {
-        let advent1_riddle1 = Advent1Riddle1::new("./data/input/1.txt");
-        let advent1_riddle2 = Advent1Riddle2::new("./data/input/1.txt");
-        let advent2_riddle1 = Advent2Riddle1::new("./data/input/2.txt");
-        let advent2_riddle2 = Advent2Riddle2::new("./data/input/2.txt");
-        let advent3_riddle1 = Advent3Riddle1::new("./data/input/3.txt");
-        let advent3_riddle2 = Advent3Riddle2::new("./data/input/3.txt");
+        RiddleContainer::with_entries(registered_riddles())
+    }
+
+    /// A container for the given riddles instead of all registered ones.
+    pub fn with_entries<I>(entries: I) -> RiddleContainer
+    where
+        I: IntoIterator<Item = &'static RiddleEntry>,
+    {
+        RiddleContainer::with_config(Config::default(), entries)
+    }
+
+    /// A container for the given riddles, which read their default inputs
+    /// from the data directory and the input set of `config`. Of several
+    /// riddles with the same key, the first one is found by that key.
+    pub fn with_config<I>(config: Config, entries: I) -> RiddleContainer
+    where
+        I: IntoIterator<Item = &'static RiddleEntry>,
+    {
+        let mut entries: Vec<&'static RiddleEntry> = entries.into_iter().collect();
+        entries.sort_by_key(|entry| (entry.day, entry.part));
+
+        let mut by_key = HashMap::new();
+        for (i, entry) in entries.iter().enumerate() {
+            by_key.entry(entry.key()).or_insert(i);
+        }
+        let inputs: Vec<String> = entries
+            .iter()
+            .map(|entry| config.input_path(entry.default_input))
+            .collect();
+        // Riddles of the same day with the same input parse it only once.
+        let mut shared: HashMap<&str, SharedInputs> = HashMap::new();
+        let riddles = entries
+            .into_iter()
+            .zip(&inputs)
+            .map(|(entry, input)| {
+                let shared = shared.entry(input).or_default();
+                (entry, (entry.build)(Input::path(input), shared))
+            })
+            .collect();
 
         RiddleContainer {
-            advent1_riddle1,
-            advent1_riddle2,
-            advent2_riddle1,
-            advent2_riddle2,
-            advent3_riddle1,
-            advent3_riddle2,
+            config,
+            riddles,
+            inputs,
+            by_key,
+        }
+    }
+
+    /// Where the inputs and answers are read from.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// The path of the default input of a riddle.
+    pub fn input_path(&self, entry: &RiddleEntry) -> String {
+        self.config.input_path(entry.default_input)
+    }
+
+    /// The riddle at `i`, unless its default input is missing.
+    fn built(&self, i: usize) -> Result<&dyn Riddle, RiddleError> {
+        let input = &self.inputs[i];
+        if !Path::new(input).is_file() {
+            return Err(RiddleError::MissingInput(input.clone()));
+        }
+        Ok(self.riddles[i].1.as_ref())
+    }
+
+    /// The registered riddles, ordered by day and part.
+    pub fn entries(&self) -> impl Iterator<Item = &'static RiddleEntry> + '_ {
+        self.riddles.iter().map(|(entry, _)| *entry)
+    }
+
+    /// The riddle registered for `key`, for example `1.2`.
+    pub fn entry(&self, key: &str) -> Option<&'static RiddleEntry> {
+        self.by_key.get(key).map(|&i| self.riddles[i].0)
+    }
+
+    /// The riddle registered for `key`, built with its default input. Fails
+    /// if the riddle is unknown or its default input is missing.
+    pub fn riddle(&self, key: &str) -> Result<&dyn Riddle, RiddleError> {
+        match self.by_key.get(key) {
+            Some(&i) => self.built(i),
+            None => Err(RiddleError::UnknownRiddle(key.to_string())),
         }
     }
 
     pub fn solve_riddle(&self, riddle: String, args: &[String]) -> Result<Solution, RiddleError> {
-        let riddle_map = {
-            let mut riddle_map: HashMap<
-                String,
-                Box<dyn Fn(&[String]) -> Result<Solution, RiddleError>>,
-            > = HashMap::new();
-
-            riddle_map.insert(
-                "1.1".to_string(),
-                Box::new(|args| self.advent1_riddle1.solve(args)),
-            );
-            riddle_map.insert(
-                "1.2".to_string(),
-                Box::new(|args| self.advent1_riddle2.solve(args)),
-            );
-            riddle_map.insert(
-                "2.1".to_string(),
-                Box::new(|args| self.advent2_riddle1.solve(args)),
-            );
-            riddle_map.insert(
-                "2.2".to_string(),
-                Box::new(|args| self.advent2_riddle2.solve(args)),
-            );
-            riddle_map.insert(
-                "3.1".to_string(),
-                Box::new(|args| self.advent3_riddle1.solve(args)),
-            );
-            riddle_map.insert(
-                "3.2".to_string(),
-                Box::new(|args| self.advent3_riddle2.solve(args)),
-            );
-
-            riddle_map
+        self.riddle(&riddle)?.solve(args)
+    }
+
+    /// Solves all riddles with their default inputs on up to `jobs` threads.
+    /// A riddle that fails or panics does not stop the others. The runs are
+    /// returned in the order of the riddles.
+    pub fn solve_all(&self, jobs: usize) -> Vec<RiddleRun> {
+        let next = AtomicUsize::new(0);
+        let runs = Mutex::new(Vec::with_capacity(self.riddles.len()));
+        let worker = || loop {
+            let i = next.fetch_add(1, Ordering::Relaxed);
+            let entry = match self.riddles.get(i) {
+                Some((entry, _)) => *entry,
+                None => break,
+            };
+            let start = Instant::now();
+            let solve = || self.built(i).and_then(|riddle| riddle.solve_timed(&[]));
+            let result = panic::catch_unwind(AssertUnwindSafe(solve))
+                .unwrap_or_else(|payload| Err(panicked(payload.as_ref())));
+            let (result, timings) = match result {
+                Ok((solution, timings)) => (Ok(solution), timings),
+                Err(riddle_err) => {
+                    let timings = Timings {
+                        parse: None,
+                        solve: start.elapsed(),
+                    };
+                    (Err(riddle_err), timings)
+                }
+            };
+            let run = RiddleRun {
+                entry,
+                result,
+                timings,
+            };
+            runs.lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push((i, run));
         };
+        thread::scope(|scope| {
+            for _ in 1..jobs.clamp(1, self.riddles.len().max(1)) {
+                scope.spawn(worker);
+            }
+            worker();
+        });
 
-        if riddle_map.contains_key(&riddle) {
-            riddle_map[&riddle](args)
-        } else {
-            Err(RiddleError::UnknownRiddle(riddle))
+        let mut runs = runs.into_inner().unwrap_or_else(PoisonError::into_inner);
+        runs.sort_by_key(|(i, _)| *i);
+        runs.into_iter().map(|(_, run)| run).collect()
+    }
+
+    /// Solves a riddle with the given input instead of its default input.
+    pub fn solve_riddle_with_input(
+        &self,
+        riddle: String,
+        input: Input,
+        args: &[String],
+    ) -> Result<Solution, RiddleError> {
+        match self.entry(&riddle) {
+            Some(entry) => (entry.build)(input, &mut SharedInputs::default()).solve(args),
+            None => Err(RiddleError::UnknownRiddle(riddle)),
         }
     }
+
+    /// Benchmarks a riddle with its default input, see `bench::bench`.
+    pub fn bench_riddle(
+        &self,
+        riddle: String,
+        args: &[String],
+        warmup: usize,
+        runs: usize,
+    ) -> Result<BenchResult, RiddleError> {
+        bench(&riddle, self.riddle(&riddle)?, args, warmup, runs)
+    }
+
+    /// Benchmarks a riddle with the given input instead of its default input.
+    pub fn bench_riddle_with_input(
+        &self,
+        riddle: String,
+        input: Input,
+        args: &[String],
+        warmup: usize,
+        runs: usize,
+    ) -> Result<BenchResult, RiddleError> {
+        match self.entry(&riddle) {
+            Some(entry) => {
+                let built = (entry.build)(input, &mut SharedInputs::default());
+                bench(&riddle, built.as_ref(), args, warmup, runs)
+            }
+            None => Err(RiddleError::UnknownRiddle(riddle)),
+        }
+    }
+}
+
+/// The error of a riddle that panicked, with the message of the panic.
+fn panicked(payload: &(dyn Any + Send)) -> RiddleError {
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("no message");
+    RiddleError::Generic(format!("the riddle panicked: {}", message))
+}
+
+impl Default for RiddleContainer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[cfg(test)]
@@ -87,4 +242,232 @@ mod riddle_container_test {
             assert_eq!(1, 1);
         }
     }
+
+    mod entries_tests {
+        use std::collections::HashSet;
+
+        use super::super::*;
+
+        #[test]
+        fn it_lists_the_riddles_in_order() {
+            let riddles = RiddleContainer::new();
+            let keys: Vec<String> = riddles.entries().map(RiddleEntry::key).collect();
+            assert_eq!(keys, vec!["1.1", "1.2", "2.1", "2.2", "3.1", "3.2"]);
+        }
+
+        #[test]
+        fn it_finds_riddles_by_key() {
+            let riddles = RiddleContainer::new();
+            let entry = riddles.entry("3.2").unwrap();
+            assert_eq!((entry.day, entry.part), (3, 2));
+            assert_eq!(entry.name, "Crossed Wires");
+            assert_eq!(entry.default_input, "3.txt");
+            assert_eq!(riddles.input_path(entry), "./data/input/3.txt");
+            assert!(riddles.entry("4.1").is_none());
+        }
+
+        #[test]
+        fn registered_riddles_have_unique_keys() {
+            let mut keys = HashSet::new();
+            for entry in registered_riddles() {
+                assert!(
+                    keys.insert(entry.key()),
+                    "{} is registered twice",
+                    entry.key()
+                );
+            }
+        }
+
+        #[test]
+        fn it_finds_the_first_of_duplicate_riddles() {
+            let entries = registered_riddles().chain(registered_riddles());
+            let riddles = RiddleContainer::with_entries(entries);
+            assert_eq!(riddles.entries().count(), 12);
+            assert!(std::ptr::eq(
+                riddles.entry("2.1").unwrap(),
+                registered_riddles().nth(2).unwrap()
+            ));
+        }
+    }
+
+    mod solve_riddle_tests {
+        use super::super::super::{RiddleError, Solution};
+        use super::super::*;
+
+        #[test]
+        fn it_solves_riddles_with_their_default_input() {
+            let riddles = RiddleContainer::new();
+            let solution = riddles.solve_riddle("2.1".to_string(), &[]);
+
+            assert_eq!(solution.unwrap(), Solution::Number(4_023_471));
+        }
+
+        #[test]
+        fn it_rejects_unknown_riddles() {
+            let riddles = RiddleContainer::new();
+            match riddles.solve_riddle("0.1".to_string(), &[]) {
+                Err(RiddleError::UnknownRiddle(riddle)) => assert_eq!(riddle, "0.1"),
+                other => panic!("expected an unknown riddle, got {:?}", other),
+            }
+        }
+    }
+
+    mod config_tests {
+        use std::fs;
+
+        use super::super::super::super::testing::TempDir;
+        use super::super::super::config::Config;
+        use super::super::super::{RiddleError, Solution};
+        use super::super::*;
+
+        #[test]
+        fn it_reads_the_inputs_of_a_profile() {
+            let data_dir = TempDir::new("container-profile-test");
+            let inputs = data_dir.join("input").join("alice");
+            fs::create_dir_all(&inputs).unwrap();
+            fs::write(inputs.join("3.txt"), "R8,U5,L5,D3\nU7,R6,D4,L4\n").unwrap();
+            let config = Config {
+                data_dir: data_dir.path().to_string_lossy().into_owned(),
+                profile: Some("alice".to_string()),
+            };
+            let riddles = RiddleContainer::with_config(config, registered_riddles());
+
+            let solution = riddles.solve_riddle("3.1".to_string(), &[]);
+            assert_eq!(solution.unwrap(), Solution::Number(6));
+            match riddles.solve_riddle("2.1".to_string(), &[]) {
+                Err(RiddleError::MissingInput(path)) => {
+                    assert_eq!(path, inputs.join("2.txt").to_string_lossy())
+                }
+                other => panic!("expected a missing input, got {:?}", other),
+            }
+        }
+    }
+
+    mod solve_all_tests {
+        use super::super::super::advent2::Advent2Riddle1;
+        use super::super::super::args::NO_ARGS;
+        use super::super::super::{RiddleError, Solution};
+        use super::super::*;
+
+        static MISSING_INPUT: RiddleEntry = RiddleEntry {
+            day: 2,
+            part: 3,
+            name: "Missing input",
+            default_input: "does-not-exist.txt",
+            args: &NO_ARGS,
+            build: |input, shared| Box::new(Advent2Riddle1::build(input, shared)),
+        };
+
+        #[test]
+        fn it_solves_all_riddles_in_order() {
+            let riddles = RiddleContainer::new();
+            for jobs in &[1, 4] {
+                let runs = riddles.solve_all(*jobs);
+                let keys: Vec<String> = runs.iter().map(|run| run.entry.key()).collect();
+                assert_eq!(keys, vec!["1.1", "1.2", "2.1", "2.2", "3.1", "3.2"]);
+                assert_eq!(runs[5].result.as_ref().unwrap(), &Solution::Number(164012));
+            }
+        }
+
+        struct Panicking;
+
+        impl Riddle for Panicking {
+            fn solve(&self, _: &[String]) -> Result<Solution, RiddleError> {
+                panic!("out of fuel")
+            }
+        }
+
+        static PANICKING: RiddleEntry = RiddleEntry {
+            day: 1,
+            part: 3,
+            name: "Panicking",
+            default_input: "1.txt",
+            args: &NO_ARGS,
+            build: |_, _| Box::new(Panicking),
+        };
+
+        #[test]
+        fn it_turns_panics_into_failed_runs() {
+            let entries = registered_riddles().chain(Some(&PANICKING));
+            let riddles = RiddleContainer::with_entries(entries);
+            let runs = riddles.solve_all(2);
+            assert_eq!(runs.len(), 7);
+            assert_eq!(runs[2].entry.key(), "1.3");
+            match &runs[2].result {
+                Err(RiddleError::Generic(message)) => {
+                    assert_eq!(message, "the riddle panicked: out of fuel")
+                }
+                other => panic!("expected a panic, got {:?}", other),
+            }
+            assert_eq!(runs.iter().filter(|run| run.result.is_ok()).count(), 6);
+        }
+
+        #[test]
+        fn it_keeps_going_after_failures() {
+            let entries = registered_riddles().chain(Some(&MISSING_INPUT));
+            let riddles = RiddleContainer::with_entries(entries);
+            let runs = riddles.solve_all(3);
+            assert_eq!(runs.len(), 7);
+            assert_eq!(runs[4].entry.key(), "2.3");
+            match &runs[4].result {
+                Err(RiddleError::MissingInput(path)) => {
+                    assert_eq!(path, "./data/input/does-not-exist.txt")
+                }
+                other => panic!("expected a missing input, got {:?}", other),
+            }
+            assert_eq!(runs.iter().filter(|run| run.result.is_ok()).count(), 6);
+        }
+    }
+
+    mod solve_riddle_with_input_tests {
+        use super::super::super::super::io::Input;
+        use super::super::super::{RiddleError, Solution};
+        use super::super::*;
+
+        #[test]
+        fn it_uses_the_input() {
+            let riddles = RiddleContainer::new();
+            let input = Input::text("R8,U5,L5,D3\nU7,R6,D4,L4\n");
+            let solution = riddles.solve_riddle_with_input("3.1".to_string(), input, &[]);
+
+            assert_eq!(solution.unwrap(), Solution::Number(6));
+        }
+
+        #[test]
+        fn it_rejects_unknown_riddles() {
+            let riddles = RiddleContainer::new();
+            let input = Input::text("");
+            match riddles.solve_riddle_with_input("9.9".to_string(), input, &[]) {
+                Err(RiddleError::UnknownRiddle(riddle)) => assert_eq!(riddle, "9.9"),
+                other => panic!("expected an unknown riddle, got {:?}", other),
+            }
+        }
+    }
+
+    mod bench_riddle_tests {
+        use super::super::super::super::io::Input;
+        use super::super::super::RiddleError;
+        use super::super::*;
+
+        #[test]
+        fn it_benchmarks_riddles() {
+            let riddles = RiddleContainer::new();
+            let result = riddles.bench_riddle("2.1".to_string(), &[], 0, 2).unwrap();
+            assert_eq!((result.riddle.as_str(), result.runs), ("2.1", 2));
+            assert!(result.parse.is_some());
+
+            let input = Input::text("R8,U5,L5,D3\nU7,R6,D4,L4\n");
+            let result = riddles.bench_riddle_with_input("3.2".to_string(), input, &[], 1, 1);
+            assert_eq!(result.unwrap().runs, 1);
+        }
+
+        #[test]
+        fn it_rejects_unknown_riddles() {
+            let riddles = RiddleContainer::new();
+            assert!(matches!(
+                riddles.bench_riddle("9.9".to_string(), &[], 0, 1),
+                Err(RiddleError::UnknownRiddle(_))
+            ));
+        }
+    }
 }
@@ -0,0 +1,39 @@
+//! Helpers shared by the unit tests.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static TEMP_DIRS: AtomicUsize = AtomicUsize::new(0);
+
+/// A directory of its own under the temporary directory, removed again when
+/// it is dropped, even if the test fails.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Creates the directory. Its name ends with the process id and a
+    /// counter, so tests running in parallel never share one.
+    pub fn new(name: &str) -> TempDir {
+        let count = TEMP_DIRS.fetch_add(1, Ordering::Relaxed);
+        let dir = format!("aoc19-{}-{}-{}", name, process::id(), count);
+        let path = env::temp_dir().join(dir);
+        fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn join<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        self.0.join(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}